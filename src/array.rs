@@ -0,0 +1,441 @@
+//! Provides [`ArraySlab<T, N>`], an object based allocator backed by a
+//! fixed-capacity inline array of slots.
+//!
+//! [`ArraySlab<T, N>`]: struct.ArraySlab.html
+
+use core::fmt;
+use core::mem::MaybeUninit;
+use core::ops::{Index, IndexMut};
+use core::ptr;
+use core::slice;
+
+use crate::{Iter, IterMut, Slot};
+
+/// An object based allocator backed by a fixed-capacity inline array of
+/// slots.
+///
+/// Unlike [`Slab<T>`], an `ArraySlab<T, N>` never allocates: its `N` slots
+/// are stored inline, so it is available without the `alloc` feature and
+/// can be placed in a `static`. Inserting an object into a full slab hands
+/// the object back instead of growing.
+///
+/// # Examples
+/// ```
+/// # use ruyi_slab::ArraySlab;
+/// let mut slab: ArraySlab<_, 2> = ArraySlab::new();
+/// let one = slab.insert(1).unwrap();
+/// let two = slab.insert(2).unwrap();
+///
+/// assert_eq!(slab.insert(3), Err(3));
+///
+/// slab.remove(one);
+///
+/// assert_eq!(slab[two], 2);
+/// assert_eq!(slab.insert(3), Ok(one));
+/// ```
+///
+/// [`Slab<T>`]: struct.Slab.html
+pub struct ArraySlab<T, const N: usize> {
+    slots: [MaybeUninit<Slot<T>>; N],
+    init: usize,
+    len: usize,
+    free: usize,
+}
+
+impl<T, const N: usize> ArraySlab<T, N> {
+    const NULL: usize = usize::MAX;
+
+    /// Constructs a new empty `ArraySlab<T, N>`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use ruyi_slab::ArraySlab;
+    /// static SLAB: ArraySlab<u32, 16> = ArraySlab::new();
+    ///
+    /// assert!(SLAB.is_empty());
+    /// ```
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            slots: [const { MaybeUninit::uninit() }; N],
+            init: 0,
+            len: 0,
+            free: Self::NULL,
+        }
+    }
+
+    /// Returns the number of objects in the slab.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::ArraySlab;
+    /// let mut slab: ArraySlab<_, 4> = ArraySlab::new();
+    /// slab.insert(1).unwrap();
+    /// slab.insert(2).unwrap();
+    ///
+    /// assert_eq!(slab.len(), 2);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns the number of objects the slab can hold, i.e. `N`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::ArraySlab;
+    /// let slab: ArraySlab<i32, 10> = ArraySlab::new();
+    ///
+    /// assert_eq!(slab.capacity(), 10);
+    /// ```
+    #[inline]
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Returns `true` if the slab contains no objects.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::ArraySlab;
+    /// let mut slab: ArraySlab<_, 4> = ArraySlab::new();
+    ///
+    /// assert!(slab.is_empty());
+    ///
+    /// slab.insert(1).unwrap();
+    ///
+    /// assert!(!slab.is_empty());
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if the slab cannot hold any more objects.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::ArraySlab;
+    /// let mut slab: ArraySlab<_, 1> = ArraySlab::new();
+    ///
+    /// assert!(!slab.is_full());
+    ///
+    /// slab.insert(1).unwrap();
+    ///
+    /// assert!(slab.is_full());
+    /// ```
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.len() == N
+    }
+
+    /// Clears the slab, removing all objects.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::ArraySlab;
+    /// let mut slab: ArraySlab<_, 4> = ArraySlab::new();
+    /// slab.insert(1).unwrap();
+    /// slab.insert(2).unwrap();
+    /// slab.clear();
+    ///
+    /// assert!(slab.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        let slots: *mut [Slot<T>] = self.slots_mut();
+        self.init = 0;
+        self.len = 0;
+        self.free = Self::NULL;
+        unsafe {
+            ptr::drop_in_place(slots);
+        }
+    }
+
+    /// Inserts an object to the slab, returning its index. If the slab is
+    /// full, the object is handed back as an error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::ArraySlab;
+    /// let mut slab: ArraySlab<_, 1> = ArraySlab::new();
+    /// let one = slab.insert(1).unwrap();
+    ///
+    /// assert_eq!(slab[one], 1);
+    /// assert_eq!(slab.insert(2), Err(2));
+    /// ```
+    #[inline]
+    pub fn insert(&mut self, obj: T) -> Result<usize, T> {
+        let cur;
+        if self.free != Self::NULL {
+            cur = self.free;
+            self.free = unsafe { self.slots_mut().get_unchecked_mut(cur).put(obj) };
+        } else if self.init < N {
+            cur = self.init;
+            unsafe {
+                self.slots
+                    .get_unchecked_mut(cur)
+                    .as_mut_ptr()
+                    .write(Slot::Used(obj));
+            }
+            self.init += 1;
+        } else {
+            return Err(obj);
+        }
+        self.len += 1;
+        Ok(cur)
+    }
+
+    /// Removes and returns the object at the specified `index`, and the slot
+    /// will be put to the list of free slots for reusing. `None` is returned
+    /// if no object is found at the specified `index`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::ArraySlab;
+    /// let mut slab: ArraySlab<_, 1> = ArraySlab::new();
+    /// let one = slab.insert(1).unwrap();
+    ///
+    /// assert_eq!(slab.remove(one), Some(1));
+    /// assert!(slab.is_empty());
+    /// ```
+    #[inline]
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        let free = self.free;
+        if let Some(slot) = self.slots_mut().get_mut(index) {
+            if let Slot::Used(_) = slot {
+                let obj = unsafe { slot.take(free) };
+                self.free = index;
+                self.len -= 1;
+                return Some(obj);
+            }
+        }
+        None
+    }
+
+    /// Returns a reference to the object at the specified `index` if the
+    /// object exists. Otherwise, `None` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::ArraySlab;
+    /// let mut slab: ArraySlab<_, 2> = ArraySlab::new();
+    /// let one = slab.insert(1).unwrap();
+    ///
+    /// assert_eq!(slab.get(one), Some(&1));
+    /// assert_eq!(slab.get(1), None);
+    /// ```
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if let Some(Slot::Used(obj)) = self.slots().get(index) {
+            return Some(obj);
+        }
+        None
+    }
+
+    /// Returns a mutable reference to the object at the specified `index`
+    /// if the object exists. Otherwise, `None` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::ArraySlab;
+    /// let mut slab: ArraySlab<_, 2> = ArraySlab::new();
+    /// let one = slab.insert(1).unwrap();
+    ///
+    /// *slab.get_mut(one).unwrap() = 2;
+    ///
+    /// assert_eq!(slab[one], 2);
+    /// ```
+    #[inline]
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if let Some(Slot::Used(obj)) = self.slots_mut().get_mut(index) {
+            return Some(obj);
+        }
+        None
+    }
+
+    /// Returns a reference to the object at the specified `index` without
+    /// checking if the object exists or not.
+    ///
+    /// # Safety
+    ///
+    /// If the slot at the specified `index` does not have an object, the
+    /// behavior of calling this method is undefined even if the resulting
+    /// reference is not used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::ArraySlab;
+    /// let mut slab: ArraySlab<_, 1> = ArraySlab::new();
+    /// let one = slab.insert(1).unwrap();
+    ///
+    /// unsafe {
+    ///     assert_eq!(slab.get_unchecked(one), &1);
+    /// }
+    /// ```
+    #[inline]
+    pub unsafe fn get_unchecked(&self, index: usize) -> &T {
+        self.slots().get_unchecked(index).get_unchecked()
+    }
+
+    /// Returns a mutable reference to the object at the specified `index`
+    /// without checking if the object exists or not.
+    ///
+    /// # Safety
+    ///
+    /// If the slot at the specified `index` does not have an object, the
+    /// behavior of calling this method is undefined even if the resulting
+    /// reference is not used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::ArraySlab;
+    /// let mut slab: ArraySlab<_, 1> = ArraySlab::new();
+    /// let one = slab.insert(1).unwrap();
+    ///
+    /// unsafe {
+    ///     *slab.get_unchecked_mut(one) = 2;
+    /// }
+    ///
+    /// assert_eq!(slab[one], 2);
+    /// ```
+    #[inline]
+    pub unsafe fn get_unchecked_mut(&mut self, index: usize) -> &mut T {
+        self.slots_mut()
+            .get_unchecked_mut(index)
+            .get_unchecked_mut()
+    }
+
+    /// Returns an iterator over the objects in the slab, along with their
+    /// indices, in ascending order of index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::ArraySlab;
+    /// let mut slab: ArraySlab<_, 2> = ArraySlab::new();
+    /// let one = slab.insert(1).unwrap();
+    /// let two = slab.insert(2).unwrap();
+    ///
+    /// let mut iter = slab.iter();
+    ///
+    /// assert_eq!(iter.next(), Some((one, &1)));
+    /// assert_eq!(iter.next(), Some((two, &2)));
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter::new(self.slots(), self.len)
+    }
+
+    /// Returns an iterator that allows modifying the objects in the slab,
+    /// along with their indices, in ascending order of index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::ArraySlab;
+    /// let mut slab: ArraySlab<_, 2> = ArraySlab::new();
+    /// let one = slab.insert(1).unwrap();
+    ///
+    /// for (_, obj) in slab.iter_mut() {
+    ///     *obj += 1;
+    /// }
+    ///
+    /// assert_eq!(slab[one], 2);
+    /// ```
+    #[inline]
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        let len = self.len;
+        IterMut::new(self.slots_mut(), len)
+    }
+
+    /// Returns the slots initialized so far.
+    #[inline]
+    fn slots(&self) -> &[Slot<T>] {
+        unsafe { slice::from_raw_parts(self.slots.as_ptr() as *const Slot<T>, self.init) }
+    }
+
+    /// Returns the slots initialized so far.
+    #[inline]
+    fn slots_mut(&mut self) -> &mut [Slot<T>] {
+        unsafe { slice::from_raw_parts_mut(self.slots.as_mut_ptr() as *mut Slot<T>, self.init) }
+    }
+}
+
+impl<T, const N: usize> Default for ArraySlab<T, N> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Drop for ArraySlab<T, N> {
+    #[inline]
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for ArraySlab<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArraySlab")
+            .field("slots", &self.slots())
+            .field("len", &self.len)
+            .field("free", &self.free)
+            .finish()
+    }
+}
+
+impl<T, const N: usize> Index<usize> for ArraySlab<T, N> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, index: usize) -> &Self::Output {
+        match self.get(index) {
+            Some(obj) => obj,
+            None => panic!("invalid slab index {}", index),
+        }
+    }
+}
+
+impl<T, const N: usize> IndexMut<usize> for ArraySlab<T, N> {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        match self.get_mut(index) {
+            Some(obj) => obj,
+            None => panic!("invalid slab index {}", index),
+        }
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a ArraySlab<T, N> {
+    type Item = (usize, &'a T);
+    type IntoIter = Iter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a mut ArraySlab<T, N> {
+    type Item = (usize, &'a mut T);
+    type IntoIter = IterMut<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
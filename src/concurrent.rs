@@ -0,0 +1,307 @@
+//! Provides [`ConcurrentSlab<T>`], a slab allocator which can be shared
+//! between threads.
+//!
+//! [`ConcurrentSlab<T>`]: struct.ConcurrentSlab.html
+
+use std::fmt;
+use std::ops::Deref;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::thread;
+
+use crate::Slab;
+
+/// An object based allocator like [`Slab<T>`], but inserting, accessing and
+/// removing objects through a shared reference, so that it can be used from
+/// many threads at once.
+///
+/// The slots are split into shards, each of which is a [`Slab<T>`] with its
+/// own list of free slots behind its own lock. A thread always inserts into
+/// the same shard, so threads inserting concurrently rarely contend with
+/// each other. The returned index encodes the shard as well as the slot in
+/// the shard, which allows any thread to access or remove the object later.
+///
+/// # Examples
+/// ```
+/// # use ruyi_slab::ConcurrentSlab;
+/// use std::sync::Arc;
+/// use std::thread;
+///
+/// let slab = Arc::new(ConcurrentSlab::new());
+///
+/// let handles: Vec<_> = (0..4)
+///     .map(|i| {
+///         let slab = slab.clone();
+///         thread::spawn(move || slab.insert(i))
+///     })
+///     .collect();
+///
+/// for (i, handle) in handles.into_iter().enumerate() {
+///     let index = handle.join().unwrap();
+///     assert_eq!(*slab.get(index).unwrap(), i);
+/// }
+/// assert_eq!(slab.len(), 4);
+/// ```
+///
+/// [`Slab<T>`]: struct.Slab.html
+#[derive(Debug)]
+pub struct ConcurrentSlab<T> {
+    shards: Box<[Mutex<Slab<T>>]>,
+}
+
+impl<T> ConcurrentSlab<T> {
+    /// Constructs a new empty `ConcurrentSlab<T>` with one shard per
+    /// available CPU. The allocator will not allocate any slot until the
+    /// first object is inserted.
+    ///
+    /// # Examples
+    /// ```
+    /// # use ruyi_slab::ConcurrentSlab;
+    /// let slab = ConcurrentSlab::new();
+    /// # slab.insert(1);
+    /// ```
+    #[inline]
+    pub fn new() -> Self {
+        let shards = thread::available_parallelism().map_or(1, |n| n.get());
+        Self::with_shards(shards)
+    }
+
+    /// Constructs a new empty `ConcurrentSlab<T>` with the specified number
+    /// of shards.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shards` is 0.
+    ///
+    /// # Examples
+    /// ```
+    /// # use ruyi_slab::ConcurrentSlab;
+    /// let slab = ConcurrentSlab::with_shards(16);
+    /// # slab.insert(1);
+    /// ```
+    pub fn with_shards(shards: usize) -> Self {
+        assert!(shards > 0, "number of shards must be non-zero");
+        Self {
+            shards: (0..shards).map(|_| Mutex::new(Slab::new())).collect(),
+        }
+    }
+
+    /// Returns the number of objects in the slab.
+    ///
+    /// Since other threads may insert or remove objects at the same time,
+    /// the returned value is only a snapshot.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::ConcurrentSlab;
+    /// let slab = ConcurrentSlab::new();
+    /// slab.insert(1);
+    /// slab.insert(2);
+    ///
+    /// assert_eq!(slab.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.shards.iter().map(|shard| lock(shard).len()).sum()
+    }
+
+    /// Returns `true` if the slab contains no objects.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::ConcurrentSlab;
+    /// let slab = ConcurrentSlab::new();
+    ///
+    /// assert!(slab.is_empty());
+    ///
+    /// slab.insert(1);
+    ///
+    /// assert!(!slab.is_empty());
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.shards.iter().all(|shard| lock(shard).is_empty())
+    }
+
+    /// Inserts an object to the shard of the current thread, returning the
+    /// index of the object.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index does not fit in `usize`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::ConcurrentSlab;
+    /// let slab = ConcurrentSlab::new();
+    /// let one = slab.insert(1);
+    ///
+    /// assert_eq!(*slab.get(one).unwrap(), 1);
+    /// ```
+    pub fn insert(&self, obj: T) -> usize {
+        let shard = thread_shard() % self.shards.len();
+        let mut guard = lock(&self.shards[shard]);
+        // Encodes the index before inserting, so that the object is not left
+        // unreachable in the shard when the index does not fit.
+        let entry = guard.free_entry();
+        let index = entry
+            .index()
+            .checked_mul(self.shards.len())
+            .and_then(|index| index.checked_add(shard))
+            .expect("slab key space exhausted");
+        entry.insert(obj);
+        index
+    }
+
+    /// Returns a reference to the object at the specified `index` if the
+    /// object exists. Otherwise, `None` is returned.
+    ///
+    /// The shard holding the object stays locked until the returned
+    /// [`ShardRef`] is dropped, so it should be kept only briefly. See
+    /// [`get_with`] for accessing the object without holding the lock
+    /// afterwards.
+    ///
+    /// # Deadlocks
+    ///
+    /// The lock of the shard is not reentrant. Calling any method of the
+    /// slab which locks the same shard while the returned [`ShardRef`] lives
+    /// on the same thread, e.g. [`insert`] which locks the shard of the
+    /// current thread, deadlocks or panics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::ConcurrentSlab;
+    /// let slab = ConcurrentSlab::with_shards(4);
+    /// let one = slab.insert(1);
+    ///
+    /// assert_eq!(slab.get(one).as_deref(), Some(&1));
+    /// assert!(slab.get(one + 4).is_none());
+    /// ```
+    ///
+    /// [`ShardRef`]: struct.ShardRef.html
+    /// [`get_with`]: #method.get_with
+    /// [`insert`]: #method.insert
+    pub fn get(&self, index: usize) -> Option<ShardRef<'_, T>> {
+        let (shard, index) = self.split(index);
+        let guard = lock(&self.shards[shard]);
+        guard.get(index)?;
+        Some(ShardRef { guard, index })
+    }
+
+    /// Calls `f` with a reference to the object at the specified `index` if
+    /// the object exists, returning the result of `f`. Otherwise, `None` is
+    /// returned.
+    ///
+    /// Unlike [`get`], the shard holding the object is unlocked once `f`
+    /// returns, so that the result can be used with the slab afterwards.
+    ///
+    /// # Deadlocks
+    ///
+    /// The shard is locked while `f` runs, so `f` must not call any method
+    /// of the slab which locks the same shard.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::ConcurrentSlab;
+    /// let slab = ConcurrentSlab::new();
+    /// let one = slab.insert(String::from("one"));
+    ///
+    /// // The clone is inserted after the shard is unlocked
+    /// let copy = slab.get_with(one, String::clone).unwrap();
+    /// let two = slab.insert(copy);
+    ///
+    /// assert_eq!(*slab.get(two).unwrap(), "one");
+    /// ```
+    ///
+    /// [`get`]: #method.get
+    pub fn get_with<F, R>(&self, index: usize, f: F) -> Option<R>
+    where
+        F: FnOnce(&T) -> R,
+    {
+        let (shard, index) = self.split(index);
+        lock(&self.shards[shard]).get(index).map(f)
+    }
+
+    /// Removes and returns the object at the specified `index`, and the slot
+    /// will be put to the list of free slots of its shard for reusing. `None`
+    /// is returned if no object is found at the specified `index`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::ConcurrentSlab;
+    /// let slab = ConcurrentSlab::new();
+    /// let one = slab.insert(1);
+    ///
+    /// assert_eq!(slab.remove(one), Some(1));
+    /// assert_eq!(slab.remove(one), None);
+    /// ```
+    pub fn remove(&self, index: usize) -> Option<T> {
+        let (shard, index) = self.split(index);
+        lock(&self.shards[shard]).remove(index)
+    }
+
+    #[inline]
+    fn split(&self, index: usize) -> (usize, usize) {
+        let shards = self.shards.len();
+        (index % shards, index / shards)
+    }
+}
+
+impl<T> Default for ConcurrentSlab<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A reference to an object in a [`ConcurrentSlab<T>`].
+///
+/// This struct is created by the [`get`] method on [`ConcurrentSlab<T>`].
+/// The shard holding the object is locked as long as the reference lives,
+/// so the same thread must not lock the shard again meanwhile.
+///
+/// [`ConcurrentSlab<T>`]: struct.ConcurrentSlab.html
+/// [`get`]: struct.ConcurrentSlab.html#method.get
+pub struct ShardRef<'a, T> {
+    guard: MutexGuard<'a, Slab<T>>,
+    index: usize,
+}
+
+impl<T> Deref for ShardRef<'_, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        // The object was found when the guard was acquired, and it cannot be
+        // removed while the shard is locked.
+        unsafe { self.guard.get_unchecked(self.index) }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ShardRef<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+#[inline]
+fn lock<T>(shard: &Mutex<Slab<T>>) -> MutexGuard<'_, Slab<T>> {
+    // No slab operation leaves the slab inconsistent when panicking, e.g.
+    // running out of memory, thus the poisoning is ignored.
+    shard.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn thread_shard() -> usize {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+
+    thread_local! {
+        static SHARD: usize = NEXT.fetch_add(1, Ordering::Relaxed);
+    }
+
+    SHARD.with(|shard| *shard)
+}
@@ -0,0 +1,467 @@
+//! Provides [`GenSlab<T>`], a slab allocator handing out generational keys
+//! which detect stale indices.
+//!
+//! [`GenSlab<T>`]: struct.GenSlab.html
+
+#[cfg(not(feature = "std"))]
+use alloc::{rc::Rc, vec::Vec};
+
+#[cfg(feature = "std")]
+use std::{rc::Rc, vec::Vec};
+
+use core::cell::RefCell;
+use core::ops::{Index, IndexMut};
+
+use crate::{OwnedSlabGuard, Slot};
+
+/// A key referring to an object in a [`GenSlab<T>`].
+///
+/// Besides the slot index, the key records the generation of the slot at
+/// the time the object was inserted. Once the object is removed, the
+/// generation of the slot is bumped, so the key no longer matches even if
+/// the slot is reused by another object.
+///
+/// [`GenSlab<T>`]: struct.GenSlab.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GenKey {
+    index: usize,
+    generation: u32,
+}
+
+impl GenKey {
+    /// Returns the index of the slot this key refers to.
+    #[inline]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the generation of the slot this key refers to.
+    #[inline]
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+#[derive(Debug)]
+struct GenSlot<T> {
+    generation: u32,
+    slot: Slot<T>,
+}
+
+/// An object based allocator like [`Slab<T>`], but returning [`GenKey`]s
+/// instead of plain indices.
+///
+/// Each slot carries a generation counter which is bumped whenever the
+/// object in the slot is removed. Accessing the slab with a key whose
+/// generation no longer matches the slot returns `None`, which prevents a
+/// stale key from silently aliasing an object inserted later into the
+/// reused slot.
+///
+/// # Examples
+/// ```
+/// # use ruyi_slab::GenSlab;
+/// let mut slab = GenSlab::new();
+/// let one = slab.insert(1);
+///
+/// assert_eq!(slab.remove(one), Some(1));
+///
+/// let two = slab.insert(2);
+///
+/// // The slot is reused, but the stale key does not match anymore
+/// assert_eq!(one.index(), two.index());
+/// assert_eq!(slab.get(one), None);
+/// assert_eq!(slab[two], 2);
+/// ```
+///
+/// [`Slab<T>`]: struct.Slab.html
+/// [`GenKey`]: struct.GenKey.html
+#[derive(Debug)]
+pub struct GenSlab<T> {
+    slots: Vec<GenSlot<T>>,
+    len: usize,
+    free: usize,
+}
+
+impl<T> GenSlab<T> {
+    const NULL: usize = usize::MAX;
+
+    /// Constructs a new empty `GenSlab<T>`.
+    /// The allocator will not allocate until the first object is inserted.
+    ///
+    /// # Examples
+    /// ```
+    /// # use ruyi_slab::GenSlab;
+    /// let mut slab = GenSlab::new();
+    /// # slab.insert(1);
+    /// ```
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            slots: Vec::new(),
+            len: 0,
+            free: Self::NULL,
+        }
+    }
+
+    /// Constructs a new, empty `GenSlab<T>` with the specified capacity.
+    ///
+    /// The slab will be able to hold exactly `capacity` objects without
+    /// reallocating. If `capacity` is 0, the slab will not allocate.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::GenSlab;
+    /// let slab: GenSlab<i32> = GenSlab::with_capacity(10);
+    ///
+    /// assert_eq!(slab.len(), 0);
+    /// assert_eq!(slab.capacity(), 10);
+    /// ```
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            slots: Vec::with_capacity(capacity),
+            len: 0,
+            free: Self::NULL,
+        }
+    }
+
+    /// Returns the number of objects in the slab.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::GenSlab;
+    /// let mut slab = GenSlab::new();
+    /// slab.insert(1);
+    /// slab.insert(2);
+    ///
+    /// assert_eq!(slab.len(), 2);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns the number of objects the slab can hold without
+    /// reallocating.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::GenSlab;
+    /// let slab: GenSlab<i32> = GenSlab::with_capacity(10);
+    ///
+    /// assert_eq!(slab.capacity(), 10);
+    /// ```
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.slots.capacity()
+    }
+
+    /// Returns `true` if the slab contains no objects.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::GenSlab;
+    /// let mut slab = GenSlab::new();
+    ///
+    /// assert!(slab.is_empty());
+    ///
+    /// slab.insert(1);
+    ///
+    /// assert!(!slab.is_empty());
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Clears the slab, removing all objects.
+    ///
+    /// Unlike [`Slab::clear`], the slots are kept so that their generations
+    /// survive, which means keys handed out before clearing never match any
+    /// object inserted afterwards. Note that this method has no effect on the
+    /// allocated capacity of the slab.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::GenSlab;
+    /// let mut slab = GenSlab::new();
+    /// let one = slab.insert(1);
+    /// slab.clear();
+    ///
+    /// assert!(slab.is_empty());
+    ///
+    /// slab.insert(2);
+    ///
+    /// assert_eq!(slab.get(one), None);
+    /// ```
+    ///
+    /// [`Slab::clear`]: struct.Slab.html#method.clear
+    pub fn clear(&mut self) {
+        self.free = Self::NULL;
+        for (index, entry) in self.slots.iter_mut().enumerate().rev() {
+            if let Slot::Used(_) = entry.slot {
+                entry.generation = entry.generation.wrapping_add(1);
+                self.len -= 1;
+            }
+            entry.slot = Slot::Free(self.free);
+            self.free = index;
+        }
+    }
+
+    /// Reserves capacity for at least `additional` more objects to be inserted
+    /// in the given `GenSlab<T>`. The slab may reserve more space to avoid
+    /// frequent reallocations.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new capacity overflows `usize`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::GenSlab;
+    /// let mut slab = GenSlab::with_capacity(1);
+    /// slab.insert(1);
+    /// slab.reserve(10);
+    ///
+    /// assert!(slab.capacity() >= 11);
+    /// ```
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        let free = self.slots.len() - self.len;
+        if additional > free {
+            self.slots.reserve(additional - free);
+        }
+    }
+
+    /// Reserves the minimum capacity for exactly `additional` more objects to
+    /// be inserted in the given `GenSlab<T>`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new capacity overflows `usize`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::GenSlab;
+    /// let mut slab = GenSlab::with_capacity(1);
+    /// slab.insert(1);
+    /// slab.reserve_exact(10);
+    ///
+    /// assert!(slab.capacity() >= 11);
+    /// ```
+    #[inline]
+    pub fn reserve_exact(&mut self, additional: usize) {
+        let free = self.slots.len() - self.len;
+        if additional > free {
+            self.slots.reserve_exact(additional - free);
+        }
+    }
+
+    /// Inserts an object to the slab, returning the key referring to it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::GenSlab;
+    /// let mut slab = GenSlab::new();
+    /// let one = slab.insert(1);
+    ///
+    /// assert_eq!(slab[one], 1);
+    /// ```
+    #[inline]
+    pub fn insert(&mut self, obj: T) -> GenKey {
+        let index;
+        let generation;
+        if self.free != Self::NULL {
+            index = self.free;
+            let entry = unsafe { self.slots.get_unchecked_mut(index) };
+            self.free = unsafe { entry.slot.put(obj) };
+            generation = entry.generation;
+        } else {
+            index = self.slots.len();
+            generation = 0;
+            self.slots.push(GenSlot {
+                generation,
+                slot: Slot::Used(obj),
+            });
+        }
+        self.len += 1;
+        GenKey { index, generation }
+    }
+
+    /// Inserts an object to the slab shared by `slab`, returning a guard
+    /// which removes the object from the slab when dropped.
+    ///
+    /// The guard holds a reference count of the slab instead of borrowing
+    /// it, so it can be stored along with other guards of the same slab.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slab is currently borrowed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::GenSlab;
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    ///
+    /// let slab = Rc::new(RefCell::new(GenSlab::new()));
+    /// let one = GenSlab::insert_owned(&slab, 1);
+    /// let two = GenSlab::insert_owned(&slab, 2);
+    ///
+    /// assert_eq!(slab.borrow().len(), 2);
+    ///
+    /// drop(one);
+    ///
+    /// assert_eq!(slab.borrow().len(), 1);
+    /// assert_eq!(*two.borrow(), 2);
+    /// ```
+    #[inline]
+    pub fn insert_owned(slab: &Rc<RefCell<Self>>, obj: T) -> OwnedSlabGuard<T> {
+        let key = slab.borrow_mut().insert(obj);
+        OwnedSlabGuard::new(slab.clone(), key)
+    }
+
+    /// Removes and returns the object referred to by the specified `key`, and
+    /// the slot will be put to the list of free slots for reusing. `None` is
+    /// returned if the key is stale or no object is found.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::GenSlab;
+    /// let mut slab = GenSlab::new();
+    /// let one = slab.insert(1);
+    ///
+    /// assert_eq!(slab.remove(one), Some(1));
+    /// assert_eq!(slab.remove(one), None);
+    /// ```
+    #[inline]
+    pub fn remove(&mut self, key: GenKey) -> Option<T> {
+        if let Some(entry) = self.slots.get_mut(key.index) {
+            if entry.generation == key.generation {
+                if let Slot::Used(_) = entry.slot {
+                    let obj = unsafe { entry.slot.take(self.free) };
+                    entry.generation = entry.generation.wrapping_add(1);
+                    self.free = key.index;
+                    self.len -= 1;
+                    return Some(obj);
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns `true` if the slab contains an object referred to by the
+    /// specified `key`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::GenSlab;
+    /// let mut slab = GenSlab::new();
+    /// let one = slab.insert(1);
+    ///
+    /// assert!(slab.contains(one));
+    ///
+    /// slab.remove(one);
+    ///
+    /// assert!(!slab.contains(one));
+    /// ```
+    #[inline]
+    pub fn contains(&self, key: GenKey) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Returns a reference to the object referred to by the specified `key`
+    /// if the object exists and the key is not stale. Otherwise, `None` is
+    /// returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::GenSlab;
+    /// let mut slab = GenSlab::new();
+    /// let one = slab.insert(1);
+    ///
+    /// assert_eq!(slab.get(one), Some(&1));
+    ///
+    /// slab.remove(one);
+    /// slab.insert(2);
+    ///
+    /// assert_eq!(slab.get(one), None);
+    /// ```
+    #[inline]
+    pub fn get(&self, key: GenKey) -> Option<&T> {
+        match self.slots.get(key.index) {
+            Some(GenSlot {
+                generation,
+                slot: Slot::Used(obj),
+            }) if *generation == key.generation => Some(obj),
+            _ => None,
+        }
+    }
+
+    /// Returns a mutable reference to the object referred to by the
+    /// specified `key` if the object exists and the key is not stale.
+    /// Otherwise, `None` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::GenSlab;
+    /// let mut slab = GenSlab::new();
+    /// let one = slab.insert(1);
+    ///
+    /// *slab.get_mut(one).unwrap() = 2;
+    ///
+    /// assert_eq!(slab[one], 2);
+    /// ```
+    #[inline]
+    pub fn get_mut(&mut self, key: GenKey) -> Option<&mut T> {
+        match self.slots.get_mut(key.index) {
+            Some(GenSlot {
+                generation,
+                slot: Slot::Used(obj),
+            }) if *generation == key.generation => Some(obj),
+            _ => None,
+        }
+    }
+}
+
+impl<T> Default for GenSlab<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Index<GenKey> for GenSlab<T> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, key: GenKey) -> &Self::Output {
+        match self.get(key) {
+            Some(obj) => obj,
+            None => panic!("invalid slab key {:?}", key),
+        }
+    }
+}
+
+impl<T> IndexMut<GenKey> for GenSlab<T> {
+    #[inline]
+    fn index_mut(&mut self, key: GenKey) -> &mut Self::Output {
+        match self.get_mut(key) {
+            Some(obj) => obj,
+            None => panic!("invalid slab key {:?}", key),
+        }
+    }
+}
@@ -0,0 +1,310 @@
+//! Provides [`SlabGuard`] and [`OwnedSlabGuard`], handles removing their
+//! objects from their slab when dropped.
+//!
+//! [`SlabGuard`]: struct.SlabGuard.html
+//! [`OwnedSlabGuard`]: struct.OwnedSlabGuard.html
+
+#[cfg(not(feature = "std"))]
+use alloc::rc::Rc;
+
+#[cfg(feature = "std")]
+use std::rc::Rc;
+
+use allocator_api2::alloc::{Allocator, Global};
+
+use core::cell::{Ref, RefCell, RefMut};
+use core::fmt;
+use core::mem::{self, ManuallyDrop};
+use core::ops::{Deref, DerefMut};
+use core::ptr;
+
+use crate::{GenKey, GenSlab, Key, Slab};
+
+/// A handle to an object in a `Slab<T>`, which removes the object from the
+/// slab when dropped.
+///
+/// This struct is created by the [`insert_guarded`] method on [`Slab<T>`].
+/// It dereferences to the object, and keeps the slab mutably borrowed as
+/// long as it lives. To keep the object in the slab, call [`into_index`] or
+/// [`forget`].
+///
+/// [`insert_guarded`]: struct.Slab.html#method.insert_guarded
+/// [`Slab<T>`]: struct.Slab.html
+/// [`into_index`]: #method.into_index
+/// [`forget`]: #method.forget
+pub struct SlabGuard<'a, T, K: Key = usize, A: Allocator = Global> {
+    slab: &'a mut Slab<T, K, A>,
+    key: K,
+}
+
+impl<'a, T, K: Key, A: Allocator> SlabGuard<'a, T, K, A> {
+    #[inline]
+    pub(crate) fn new(slab: &'a mut Slab<T, K, A>, key: K) -> Self {
+        Self { slab, key }
+    }
+
+    /// Returns the index of the object this guard refers to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab = Slab::new();
+    /// let guard = slab.insert_guarded(1);
+    /// let index = guard.index();
+    /// guard.forget();
+    ///
+    /// assert_eq!(slab[index], 1);
+    /// ```
+    #[inline]
+    pub fn index(&self) -> K {
+        self.key
+    }
+
+    /// Consumes the guard without removing the object, returning the index
+    /// of the object.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab = Slab::new();
+    /// let one = slab.insert_guarded(1).into_index();
+    ///
+    /// assert_eq!(slab[one], 1);
+    /// ```
+    #[inline]
+    pub fn into_index(self) -> K {
+        let key = self.key;
+        mem::forget(self);
+        key
+    }
+
+    /// Consumes the guard without removing the object.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab = Slab::new();
+    /// slab.insert_guarded(1).forget();
+    ///
+    /// assert_eq!(slab.len(), 1);
+    /// ```
+    #[inline]
+    pub fn forget(self) {
+        mem::forget(self);
+    }
+}
+
+impl<T, K: Key, A: Allocator> Deref for SlabGuard<'_, T, K, A> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        // The object stays in the slab as long as the guard holds the borrow.
+        unsafe { self.slab.get_unchecked(self.key) }
+    }
+}
+
+impl<T, K: Key, A: Allocator> DerefMut for SlabGuard<'_, T, K, A> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { self.slab.get_unchecked_mut(self.key) }
+    }
+}
+
+impl<T, K: Key, A: Allocator> Drop for SlabGuard<'_, T, K, A> {
+    #[inline]
+    fn drop(&mut self) {
+        self.slab.remove(self.key);
+    }
+}
+
+impl<T: fmt::Debug, K: Key + fmt::Debug, A: Allocator> fmt::Debug for SlabGuard<'_, T, K, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SlabGuard")
+            .field("index", &self.key)
+            .field("obj", &**self)
+            .finish()
+    }
+}
+
+/// A handle to an object in a `GenSlab<T>` shared by `Rc<RefCell<_>>`,
+/// which removes the object from the slab when dropped.
+///
+/// This struct is created by the [`insert_owned`] associated function on
+/// [`GenSlab<T>`]. Unlike [`SlabGuard`], it does not borrow the slab, so
+/// many of them may live at the same time. Since the slab can be borrowed by
+/// others, the object is accessed by [`borrow`] and [`borrow_mut`] instead
+/// of dereferencing the guard. To keep the object in the slab, call
+/// [`into_index`] or [`forget`].
+///
+/// The guard refers to its object by a [`GenKey`], so if the object has
+/// already been removed from the slab by other means, dropping the guard
+/// does nothing, even if the slot is reused by another object. If the slab
+/// is borrowed when the guard is dropped, e.g. when the guard is owned by
+/// another object of the slab which is being removed, the object is left in
+/// the slab instead of panicking.
+///
+/// There is no `Arc` variant of this guard: sharing a slab between threads
+/// needs a lock, and dropping a guard while the same thread holds the lock
+/// would deadlock or panic. [`ConcurrentSlab<T>`] is meant for sharing objects
+/// between threads instead.
+///
+/// [`insert_owned`]: struct.GenSlab.html#method.insert_owned
+/// [`GenSlab<T>`]: struct.GenSlab.html
+/// [`SlabGuard`]: struct.SlabGuard.html
+/// [`GenKey`]: struct.GenKey.html
+/// [`ConcurrentSlab<T>`]: struct.ConcurrentSlab.html
+/// [`borrow`]: #method.borrow
+/// [`borrow_mut`]: #method.borrow_mut
+/// [`into_index`]: #method.into_index
+/// [`forget`]: #method.forget
+pub struct OwnedSlabGuard<T> {
+    slab: Rc<RefCell<GenSlab<T>>>,
+    key: GenKey,
+}
+
+impl<T> OwnedSlabGuard<T> {
+    #[inline]
+    pub(crate) fn new(slab: Rc<RefCell<GenSlab<T>>>, key: GenKey) -> Self {
+        Self { slab, key }
+    }
+
+    /// Returns the key of the object this guard refers to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::GenSlab;
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    ///
+    /// let slab = Rc::new(RefCell::new(GenSlab::new()));
+    /// let guard = GenSlab::insert_owned(&slab, 1);
+    ///
+    /// assert_eq!(slab.borrow()[guard.index()], 1);
+    /// ```
+    #[inline]
+    pub fn index(&self) -> GenKey {
+        self.key
+    }
+
+    /// Immutably borrows the object this guard refers to.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slab is currently mutably borrowed, or the object has
+    /// been removed from the slab by other means.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::GenSlab;
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    ///
+    /// let slab = Rc::new(RefCell::new(GenSlab::new()));
+    /// let guard = GenSlab::insert_owned(&slab, 1);
+    ///
+    /// assert_eq!(*guard.borrow(), 1);
+    /// ```
+    #[inline]
+    pub fn borrow(&self) -> Ref<'_, T> {
+        let key = self.key;
+        Ref::map(self.slab.borrow(), |slab| &slab[key])
+    }
+
+    /// Mutably borrows the object this guard refers to.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slab is currently borrowed, or the object has been
+    /// removed from the slab by other means.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::GenSlab;
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    ///
+    /// let slab = Rc::new(RefCell::new(GenSlab::new()));
+    /// let guard = GenSlab::insert_owned(&slab, 1);
+    /// *guard.borrow_mut() = 2;
+    ///
+    /// assert_eq!(*guard.borrow(), 2);
+    /// ```
+    #[inline]
+    pub fn borrow_mut(&self) -> RefMut<'_, T> {
+        let key = self.key;
+        RefMut::map(self.slab.borrow_mut(), |slab| &mut slab[key])
+    }
+
+    /// Consumes the guard without removing the object, returning the key of
+    /// the object.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::GenSlab;
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    ///
+    /// let slab = Rc::new(RefCell::new(GenSlab::new()));
+    /// let one = GenSlab::insert_owned(&slab, 1).into_index();
+    ///
+    /// assert_eq!(slab.borrow()[one], 1);
+    /// ```
+    #[inline]
+    pub fn into_index(self) -> GenKey {
+        let key = self.key;
+        self.forget();
+        key
+    }
+
+    /// Consumes the guard without removing the object.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::GenSlab;
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    ///
+    /// let slab = Rc::new(RefCell::new(GenSlab::new()));
+    /// GenSlab::insert_owned(&slab, 1).forget();
+    ///
+    /// assert_eq!(slab.borrow().len(), 1);
+    /// ```
+    #[inline]
+    pub fn forget(self) {
+        let mut this = ManuallyDrop::new(self);
+        // Releases the reference to the slab without running `drop`.
+        unsafe { ptr::drop_in_place(&mut this.slab) };
+    }
+}
+
+impl<T> Drop for OwnedSlabGuard<T> {
+    /// Removes the object from the slab, unless the slab is currently
+    /// borrowed or the object has already been removed.
+    #[inline]
+    fn drop(&mut self) {
+        if let Ok(mut slab) = self.slab.try_borrow_mut() {
+            // The object is dropped after the borrow is released, so that its
+            // destructor may access the slab.
+            let obj = slab.remove(self.key);
+            drop(slab);
+            drop(obj);
+        }
+    }
+}
+
+impl<T> fmt::Debug for OwnedSlabGuard<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OwnedSlabGuard")
+            .field("index", &self.key)
+            .finish_non_exhaustive()
+    }
+}
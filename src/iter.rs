@@ -0,0 +1,126 @@
+//! Provides the iterators borrowing the objects in a slab.
+
+use core::iter::{Enumerate, FusedIterator};
+use core::slice;
+
+use crate::{Key, Slot};
+
+/// An iterator over the objects in a slab.
+///
+/// This struct is created by the `iter` method on [`Slab<T>`] and
+/// [`ArraySlab<T, N>`].
+///
+/// [`Slab<T>`]: struct.Slab.html
+/// [`ArraySlab<T, N>`]: struct.ArraySlab.html
+#[derive(Debug)]
+pub struct Iter<'a, T, K = usize> {
+    slots: Enumerate<slice::Iter<'a, Slot<T, K>>>,
+    len: usize,
+}
+
+impl<'a, T, K> Iter<'a, T, K> {
+    #[inline]
+    pub(crate) fn new(slots: &'a [Slot<T, K>], len: usize) -> Self {
+        Self {
+            slots: slots.iter().enumerate(),
+            len,
+        }
+    }
+}
+
+impl<'a, T, K: Key> Iterator for Iter<'a, T, K> {
+    type Item = (K, &'a T);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        for (index, slot) in &mut self.slots {
+            if let Slot::Used(obj) = slot {
+                self.len -= 1;
+                return Some((K::from_index(index), obj));
+            }
+        }
+        None
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T, K: Key> DoubleEndedIterator for Iter<'_, T, K> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        while let Some((index, slot)) = self.slots.next_back() {
+            if let Slot::Used(obj) = slot {
+                self.len -= 1;
+                return Some((K::from_index(index), obj));
+            }
+        }
+        None
+    }
+}
+
+impl<T, K: Key> ExactSizeIterator for Iter<'_, T, K> {}
+
+impl<T, K: Key> FusedIterator for Iter<'_, T, K> {}
+
+/// A mutable iterator over the objects in a slab.
+///
+/// This struct is created by the `iter_mut` method on [`Slab<T>`] and
+/// [`ArraySlab<T, N>`].
+///
+/// [`Slab<T>`]: struct.Slab.html
+/// [`ArraySlab<T, N>`]: struct.ArraySlab.html
+#[derive(Debug)]
+pub struct IterMut<'a, T, K = usize> {
+    slots: Enumerate<slice::IterMut<'a, Slot<T, K>>>,
+    len: usize,
+}
+
+impl<'a, T, K> IterMut<'a, T, K> {
+    #[inline]
+    pub(crate) fn new(slots: &'a mut [Slot<T, K>], len: usize) -> Self {
+        Self {
+            slots: slots.iter_mut().enumerate(),
+            len,
+        }
+    }
+}
+
+impl<'a, T, K: Key> Iterator for IterMut<'a, T, K> {
+    type Item = (K, &'a mut T);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        for (index, slot) in &mut self.slots {
+            if let Slot::Used(obj) = slot {
+                self.len -= 1;
+                return Some((K::from_index(index), obj));
+            }
+        }
+        None
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T, K: Key> DoubleEndedIterator for IterMut<'_, T, K> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        while let Some((index, slot)) = self.slots.next_back() {
+            if let Slot::Used(obj) = slot {
+                self.len -= 1;
+                return Some((K::from_index(index), obj));
+            }
+        }
+        None
+    }
+}
+
+impl<T, K: Key> ExactSizeIterator for IterMut<'_, T, K> {}
+
+impl<T, K: Key> FusedIterator for IterMut<'_, T, K> {}
@@ -0,0 +1,144 @@
+//! Provides the [`Key`] trait for strongly typed slab keys.
+//!
+//! [`Key`]: trait.Key.html
+
+/// A type that can be used as a key to access objects in a [`Slab<T, K>`].
+///
+/// A key is a thin wrapper around the index of a slot. Using distinct key
+/// types for distinct slabs prevents an index obtained from one slab being
+/// used to access another one by accident. Newtype keys are best declared
+/// with the [`slab_key!`] macro.
+///
+/// Besides keys handed out to users, the slab stores keys as the links
+/// between its free slots. A narrower key type, such as `u32`, thus reduces
+/// the memory taken by free slots, at the cost of limiting the number of
+/// slots to [`MAX`].
+///
+/// # Safety
+///
+/// Implementations must round-trip, i.e. `K::from_index(i).into_index()`
+/// must be equal to `i` for every `i` in `0..=K::MAX`. The slab relies on
+/// this to follow the links between its free slots without bounds checks.
+///
+/// # Examples
+///
+/// ```
+/// # use ruyi_slab::Key;
+/// let key = usize::from_index(3);
+///
+/// assert_eq!(key.into_index(), 3);
+/// ```
+///
+/// [`Slab<T, K>`]: struct.Slab.html
+/// [`slab_key!`]: macro.slab_key.html
+/// [`MAX`]: #associatedconstant.MAX
+pub unsafe trait Key: Copy {
+    /// The maximum number of slots addressable by this key type. It is also
+    /// used by the slab to mark the end of the list of free slots, thus the
+    /// valid indices are `0..MAX`.
+    const MAX: usize;
+
+    /// Creates a key from the specified slot `index`.
+    fn from_index(index: usize) -> Self;
+
+    /// Returns the slot index this key refers to.
+    fn into_index(self) -> usize;
+}
+
+unsafe impl Key for usize {
+    const MAX: usize = usize::MAX;
+
+    #[inline]
+    fn from_index(index: usize) -> Self {
+        index
+    }
+
+    #[inline]
+    fn into_index(self) -> usize {
+        self
+    }
+}
+
+macro_rules! impl_key {
+    ($($ty:ty),*) => {$(
+        unsafe impl Key for $ty {
+            const MAX: usize = <$ty>::MAX as usize;
+
+            #[inline]
+            fn from_index(index: usize) -> Self {
+                debug_assert!(index <= <Self as Key>::MAX);
+                index as $ty
+            }
+
+            #[inline]
+            fn into_index(self) -> usize {
+                self as usize
+            }
+        }
+    )*};
+}
+
+impl_key!(u16, u32);
+
+/// Declares newtype keys implementing the [`Key`] trait.
+///
+/// The declared types derive `Debug`, `Clone`, `Copy`, `PartialEq`, `Eq`,
+/// `PartialOrd`, `Ord` and `Hash`. Being distinct types, they prevent mixing
+/// up the keys of slabs using different key types. They do not guarantee
+/// that a key comes from a slab though, as [`Key::from_index`] creates a key
+/// from any index.
+///
+/// # Examples
+///
+/// ```
+/// # use ruyi_slab::{slab_key, Slab};
+/// slab_key! {
+///     /// Refers to a connection.
+///     pub struct ConnId(usize);
+///
+///     /// Refers to a timer.
+///     struct TimerId(usize);
+/// }
+///
+/// let mut conns: Slab<&str, ConnId> = Slab::with_key();
+/// let id = conns.insert("127.0.0.1:8080");
+///
+/// assert_eq!(conns[id], "127.0.0.1:8080");
+/// ```
+///
+/// The wrapped type determines the width of the key:
+///
+/// ```
+/// # use ruyi_slab::{slab_key, Key};
+/// slab_key! {
+///     pub struct SmallId(u16);
+/// }
+///
+/// assert_eq!(SmallId::MAX, u16::MAX as usize);
+/// assert_eq!(std::mem::size_of::<SmallId>(), 2);
+/// ```
+///
+/// [`Key`]: trait.Key.html
+/// [`Key::from_index`]: trait.Key.html#tymethod.from_index
+#[macro_export]
+macro_rules! slab_key {
+    ($($(#[$attr:meta])* $vis:vis struct $name:ident($inner:ty);)+) => {$(
+        $(#[$attr])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        $vis struct $name($inner);
+
+        unsafe impl $crate::Key for $name {
+            const MAX: usize = <$inner as $crate::Key>::MAX;
+
+            #[inline]
+            fn from_index(index: usize) -> Self {
+                $name(<$inner as $crate::Key>::from_index(index))
+            }
+
+            #[inline]
+            fn into_index(self) -> usize {
+                <$inner as $crate::Key>::into_index(self.0)
+            }
+        }
+    )+};
+}
@@ -0,0 +1,375 @@
+//! Provides [`LockFreeSlab<T>`], a fixed-capacity slab allocator whose list
+//! of free slots is a lock-free stack.
+//!
+//! [`LockFreeSlab<T>`]: struct.LockFreeSlab.html
+
+#[cfg(not(feature = "std"))]
+use alloc::{boxed::Box, vec::Vec};
+
+#[cfg(feature = "std")]
+use std::{boxed::Box, vec::Vec};
+
+use core::fmt;
+use core::mem::MaybeUninit;
+
+use self::sync::atomic::{AtomicU32, AtomicU64, AtomicU8, AtomicUsize, Ordering};
+use self::sync::UnsafeCell;
+
+/// An object based allocator like [`Slab<T>`], but with a fixed capacity
+/// and inserting and removing objects through a shared reference without
+/// locking.
+///
+/// The free slots are linked into a Treiber stack, whose head is updated
+/// with a single compare-and-swap. The head is tagged with a counter bumped
+/// on every update, so that a thread which is preempted in the middle of
+/// popping a slot notices the slot being popped and pushed back by other
+/// threads in the meantime (the ABA problem), instead of corrupting the
+/// stack.
+///
+/// Since an object may be removed by another thread at any time, shared
+/// access to the objects is only provided by the unsafe [`get_unchecked`].
+///
+/// # Examples
+/// ```
+/// # use ruyi_slab::LockFreeSlab;
+/// use std::sync::Arc;
+/// use std::thread;
+///
+/// let slab = Arc::new(LockFreeSlab::with_capacity(4));
+///
+/// let handles: Vec<_> = (0..4)
+///     .map(|i| {
+///         let slab = slab.clone();
+///         thread::spawn(move || slab.insert(i).unwrap())
+///     })
+///     .collect();
+///
+/// for handle in handles {
+///     let index = handle.join().unwrap();
+///     assert!(slab.remove(index).is_some());
+/// }
+/// assert!(slab.is_empty());
+/// ```
+///
+/// [`Slab<T>`]: struct.Slab.html
+/// [`get_unchecked`]: #method.get_unchecked
+pub struct LockFreeSlab<T> {
+    slots: Box<[AtomicSlot<T>]>,
+    // The index of the top free slot in the low half, and the tag in the
+    // high half.
+    head: AtomicU64,
+    len: AtomicUsize,
+}
+
+struct AtomicSlot<T> {
+    state: AtomicU8,
+    next: AtomicU32,
+    obj: UnsafeCell<MaybeUninit<T>>,
+}
+
+const FREE: u8 = 0;
+const BUSY: u8 = 1;
+const USED: u8 = 2;
+
+const NULL: u32 = u32::MAX;
+
+unsafe impl<T: Send> Send for LockFreeSlab<T> {}
+unsafe impl<T: Send + Sync> Sync for LockFreeSlab<T> {}
+
+impl<T> LockFreeSlab<T> {
+    /// Constructs a new, empty `LockFreeSlab<T>` which is able to hold
+    /// exactly `capacity` objects. The slab never grows.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is not less than `u32::MAX`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::LockFreeSlab;
+    /// let slab: LockFreeSlab<i32> = LockFreeSlab::with_capacity(10);
+    ///
+    /// assert_eq!(slab.len(), 0);
+    /// assert_eq!(slab.capacity(), 10);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Self {
+        assert!(capacity < NULL as usize, "capacity overflow");
+        let slots: Vec<_> = (0..capacity)
+            .map(|index| AtomicSlot {
+                state: AtomicU8::new(FREE),
+                next: AtomicU32::new(if index + 1 < capacity {
+                    (index + 1) as u32
+                } else {
+                    NULL
+                }),
+                obj: UnsafeCell::new(MaybeUninit::uninit()),
+            })
+            .collect();
+        let top = if capacity > 0 { 0 } else { NULL };
+        Self {
+            slots: slots.into_boxed_slice(),
+            head: AtomicU64::new(pack(top, 0)),
+            len: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the number of objects in the slab.
+    ///
+    /// Since other threads may insert or remove objects at the same time,
+    /// the returned value is only a snapshot.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::LockFreeSlab;
+    /// let slab = LockFreeSlab::with_capacity(2);
+    /// slab.insert(1).unwrap();
+    /// slab.insert(2).unwrap();
+    ///
+    /// assert_eq!(slab.len(), 2);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of objects the slab can hold.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::LockFreeSlab;
+    /// let slab: LockFreeSlab<i32> = LockFreeSlab::with_capacity(10);
+    ///
+    /// assert_eq!(slab.capacity(), 10);
+    /// ```
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Returns `true` if the slab contains no objects.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::LockFreeSlab;
+    /// let slab = LockFreeSlab::with_capacity(1);
+    ///
+    /// assert!(slab.is_empty());
+    ///
+    /// slab.insert(1).unwrap();
+    ///
+    /// assert!(!slab.is_empty());
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Inserts an object to the slab, returning its index. If the slab is
+    /// full, the object is given back in the `Err` variant.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::LockFreeSlab;
+    /// let slab = LockFreeSlab::with_capacity(1);
+    /// let one = slab.insert(1).unwrap();
+    ///
+    /// assert_eq!(slab.insert(2), Err(2));
+    /// assert_eq!(slab.remove(one), Some(1));
+    /// ```
+    pub fn insert(&self, obj: T) -> Result<usize, T> {
+        let index = match self.pop() {
+            Some(index) => index,
+            None => return Err(obj),
+        };
+        let slot = unsafe { self.slots.get_unchecked(index) };
+        // The slot is owned by this thread once popped from the stack.
+        slot.obj.with_mut(|ptr| unsafe { (*ptr).write(obj) });
+        self.len.fetch_add(1, Ordering::Relaxed);
+        slot.state.store(USED, Ordering::Release);
+        Ok(index)
+    }
+
+    /// Removes and returns the object at the specified `index`, and the slot
+    /// will be put to the list of free slots for reusing. `None` is returned
+    /// if no object is found at the specified `index`.
+    ///
+    /// If several threads remove the same object at once, only one of them
+    /// gets the object.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::LockFreeSlab;
+    /// let slab = LockFreeSlab::with_capacity(1);
+    /// let one = slab.insert(1).unwrap();
+    ///
+    /// assert_eq!(slab.remove(one), Some(1));
+    /// assert_eq!(slab.remove(one), None);
+    /// ```
+    pub fn remove(&self, index: usize) -> Option<T> {
+        let slot = self.slots.get(index)?;
+        slot.state
+            .compare_exchange(USED, BUSY, Ordering::Acquire, Ordering::Relaxed)
+            .ok()?;
+        let obj = slot.obj.with(|ptr| unsafe { (*ptr).assume_init_read() });
+        slot.state.store(FREE, Ordering::Relaxed);
+        self.len.fetch_sub(1, Ordering::Relaxed);
+        self.push(index);
+        Some(obj)
+    }
+
+    /// Returns a mutable reference to the object at the specified `index`
+    /// if the object exists. Otherwise, `None` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::LockFreeSlab;
+    /// let mut slab = LockFreeSlab::with_capacity(1);
+    /// let one = slab.insert(1).unwrap();
+    ///
+    /// *slab.get_mut(one).unwrap() = 2;
+    ///
+    /// assert_eq!(slab.remove(one), Some(2));
+    /// ```
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        let slot = self.slots.get(index)?;
+        if slot.state.load(Ordering::Acquire) == USED {
+            return Some(slot.obj.with_mut(|ptr| unsafe { (*ptr).assume_init_mut() }));
+        }
+        None
+    }
+
+    /// Returns a reference to the object at the specified `index`, without
+    /// doing any check.
+    ///
+    /// # Safety
+    ///
+    /// The object at the specified `index` must exist, and must not be
+    /// removed by any thread as long as the returned reference lives.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::LockFreeSlab;
+    /// let slab = LockFreeSlab::with_capacity(1);
+    /// let one = slab.insert(1).unwrap();
+    ///
+    /// assert_eq!(unsafe { slab.get_unchecked(one) }, &1);
+    /// ```
+    #[inline]
+    pub unsafe fn get_unchecked(&self, index: usize) -> &T {
+        let slot = self.slots.get_unchecked(index);
+        slot.obj.with(|ptr| (*ptr).assume_init_ref())
+    }
+
+    fn pop(&self) -> Option<usize> {
+        let mut head = self.head.load(Ordering::Acquire);
+        loop {
+            let (top, tag) = unpack(head);
+            if top == NULL {
+                return None;
+            }
+            // The slot may be popped by another thread at the same time, in
+            // which case `next` is stale, but the tag of the head has changed
+            // so that the exchange fails.
+            let next = unsafe { self.slots.get_unchecked(top as usize) }
+                .next
+                .load(Ordering::Relaxed);
+            match self.head.compare_exchange_weak(
+                head,
+                pack(next, tag.wrapping_add(1)),
+                Ordering::Acquire,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => return Some(top as usize),
+                Err(actual) => head = actual,
+            }
+        }
+    }
+
+    fn push(&self, index: usize) {
+        let slot = unsafe { self.slots.get_unchecked(index) };
+        let mut head = self.head.load(Ordering::Relaxed);
+        loop {
+            let (top, tag) = unpack(head);
+            slot.next.store(top, Ordering::Relaxed);
+            match self.head.compare_exchange_weak(
+                head,
+                pack(index as u32, tag.wrapping_add(1)),
+                Ordering::Release,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return,
+                Err(actual) => head = actual,
+            }
+        }
+    }
+}
+
+impl<T> Drop for LockFreeSlab<T> {
+    fn drop(&mut self) {
+        for slot in self.slots.iter() {
+            if slot.state.load(Ordering::Acquire) == USED {
+                slot.obj
+                    .with_mut(|ptr| unsafe { (*ptr).assume_init_drop() });
+            }
+        }
+    }
+}
+
+impl<T> fmt::Debug for LockFreeSlab<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LockFreeSlab")
+            .field("len", &self.len())
+            .field("capacity", &self.capacity())
+            .finish()
+    }
+}
+
+#[inline]
+fn pack(top: u32, tag: u32) -> u64 {
+    (tag as u64) << 32 | top as u64
+}
+
+#[inline]
+fn unpack(head: u64) -> (u32, u32) {
+    (head as u32, (head >> 32) as u32)
+}
+
+#[cfg(loom)]
+mod sync {
+    pub(crate) use loom::cell::UnsafeCell;
+    pub(crate) use loom::sync::atomic;
+}
+
+#[cfg(not(loom))]
+mod sync {
+    pub(crate) use core::sync::atomic;
+
+    // Mirrors the API of `loom::cell::UnsafeCell`, which tracks the accesses
+    // to the cell in the model tests.
+    pub(crate) struct UnsafeCell<T>(core::cell::UnsafeCell<T>);
+
+    impl<T> UnsafeCell<T> {
+        #[inline]
+        pub(crate) fn new(data: T) -> Self {
+            Self(core::cell::UnsafeCell::new(data))
+        }
+
+        #[inline]
+        pub(crate) fn with<R>(&self, f: impl FnOnce(*const T) -> R) -> R {
+            f(self.0.get())
+        }
+
+        #[inline]
+        pub(crate) fn with_mut<R>(&self, f: impl FnOnce(*mut T) -> R) -> R {
+            f(self.0.get())
+        }
+    }
+}
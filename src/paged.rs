@@ -0,0 +1,499 @@
+//! Provides [`PagedSlab<T, PAGE>`], a slab allocator whose objects never
+//! move in memory.
+//!
+//! [`PagedSlab<T, PAGE>`]: struct.PagedSlab.html
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+#[cfg(feature = "std")]
+use std::vec::Vec;
+
+use core::ops::{Index, IndexMut};
+use core::pin::Pin;
+use core::ptr;
+use core::slice;
+
+use crate::Slot;
+
+/// An object based allocator backed by a growable list of fixed-size pages
+/// of slots.
+///
+/// Unlike [`Slab<T>`], which reallocates its slots when growing, a
+/// `PagedSlab<T, PAGE>` grows by appending pages of `PAGE` slots, so an
+/// object stays at the same address from its insertion until its removal.
+/// This makes it possible to hand out raw pointers to the objects, e.g. as
+/// buffers for asynchronous I/O.
+///
+/// # Examples
+/// ```
+/// # use ruyi_slab::PagedSlab;
+/// let mut slab: PagedSlab<_, 4> = PagedSlab::new();
+/// let one = slab.insert(1);
+/// let addr = &slab[one] as *const i32;
+///
+/// // Growing the slab does not move the objects
+/// for i in 0..100 {
+///     slab.insert(i);
+/// }
+///
+/// assert_eq!(&slab[one] as *const i32, addr);
+/// ```
+///
+/// # Pinning
+///
+/// Since the objects never move, they can be accessed through pinned
+/// references with [`insert_pinned`] and [`get_pin_mut`], which allows
+/// self-referential objects such as futures to live in the slab. To uphold
+/// the pinning guarantee, the methods giving out plain mutable references or
+/// moving objects out of the slab require `T: Unpin`, while objects which
+/// are not `Unpin` are dropped in place with [`remove_in_place`].
+///
+/// [`Slab<T>`]: struct.Slab.html
+/// [`insert_pinned`]: #method.insert_pinned
+/// [`get_pin_mut`]: #method.get_pin_mut
+/// [`remove_in_place`]: #method.remove_in_place
+#[derive(Debug)]
+pub struct PagedSlab<T, const PAGE: usize = 64> {
+    pages: Vec<Vec<Slot<T>>>,
+    init: usize,
+    len: usize,
+    free: usize,
+}
+
+impl<T, const PAGE: usize> PagedSlab<T, PAGE> {
+    const NULL: usize = usize::MAX;
+
+    /// Constructs a new empty `PagedSlab<T, PAGE>`.
+    /// The allocator will not allocate until the first object is inserted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `PAGE` is 0.
+    ///
+    /// # Examples
+    /// ```
+    /// # use ruyi_slab::PagedSlab;
+    /// let mut slab: PagedSlab<_> = PagedSlab::new();
+    /// # slab.insert(1);
+    /// ```
+    #[inline]
+    pub const fn new() -> Self {
+        assert!(PAGE > 0, "page size must be non-zero");
+        Self {
+            pages: Vec::new(),
+            init: 0,
+            len: 0,
+            free: Self::NULL,
+        }
+    }
+
+    /// Constructs a new, empty `PagedSlab<T, PAGE>` with enough pages to hold
+    /// at least `capacity` objects without allocating.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `PAGE` is 0, or `capacity` rounded up to a multiple of
+    /// `PAGE` overflows `usize`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::PagedSlab;
+    /// let slab: PagedSlab<i32, 4> = PagedSlab::with_capacity(10);
+    ///
+    /// assert_eq!(slab.len(), 0);
+    /// assert_eq!(slab.capacity(), 12);
+    /// ```
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        let mut slab = Self::new();
+        slab.reserve(capacity);
+        slab
+    }
+
+    /// Returns the number of objects in the slab.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::PagedSlab;
+    /// let mut slab: PagedSlab<_> = PagedSlab::new();
+    /// slab.insert(1);
+    /// slab.insert(2);
+    ///
+    /// assert_eq!(slab.len(), 2);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns the number of objects the slab can hold without allocating
+    /// another page.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::PagedSlab;
+    /// let mut slab: PagedSlab<_, 8> = PagedSlab::new();
+    ///
+    /// assert_eq!(slab.capacity(), 0);
+    ///
+    /// slab.insert(1);
+    ///
+    /// assert_eq!(slab.capacity(), 8);
+    /// ```
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.pages.len() * PAGE
+    }
+
+    /// Returns `true` if the slab contains no objects.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::PagedSlab;
+    /// let mut slab: PagedSlab<_> = PagedSlab::new();
+    ///
+    /// assert!(slab.is_empty());
+    ///
+    /// slab.insert(1);
+    ///
+    /// assert!(!slab.is_empty());
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Clears the slab, removing all objects.
+    ///
+    /// Note that this method keeps the allocated pages of the slab.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::PagedSlab;
+    /// let mut slab: PagedSlab<_> = PagedSlab::new();
+    /// slab.insert(1);
+    /// slab.clear();
+    ///
+    /// assert!(slab.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        self.init = 0;
+        self.len = 0;
+        self.free = Self::NULL;
+
+        // Keeps clearing the other pages if dropping an object panics, so
+        // that no object is left in a page which is then pushed beyond its
+        // capacity, moving the objects.
+        struct ClearOnDrop<'a, T>(slice::IterMut<'a, Vec<Slot<T>>>);
+
+        impl<T> Drop for ClearOnDrop<'_, T> {
+            #[inline]
+            fn drop(&mut self) {
+                for page in &mut self.0 {
+                    page.clear();
+                }
+            }
+        }
+
+        let mut guard = ClearOnDrop(self.pages.iter_mut());
+        for page in &mut guard.0 {
+            page.clear();
+        }
+    }
+
+    /// Reserves pages for at least `additional` more objects to be inserted
+    /// in the given `PagedSlab<T, PAGE>`. Does nothing if capacity is already
+    /// sufficient.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the required capacity overflows `usize`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::PagedSlab;
+    /// let mut slab: PagedSlab<_, 4> = PagedSlab::new();
+    /// slab.insert(1);
+    /// slab.reserve(10);
+    ///
+    /// assert!(slab.capacity() >= 11);
+    /// ```
+    pub fn reserve(&mut self, additional: usize) {
+        let free = self.init - self.len;
+        if additional > free {
+            let pages = (additional - free)
+                .checked_add(self.init)
+                .and_then(|required| required.checked_add(PAGE - 1))
+                .expect("capacity overflow")
+                / PAGE;
+            if pages > self.pages.len() {
+                self.pages.reserve_exact(pages - self.pages.len());
+                while self.pages.len() < pages {
+                    self.pages.push(Vec::with_capacity(PAGE));
+                }
+            }
+        }
+    }
+
+    /// Inserts an object to the slab, returning its index. The object will
+    /// not move in memory until it is removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::PagedSlab;
+    /// let mut slab: PagedSlab<_> = PagedSlab::new();
+    /// let one = slab.insert(1);
+    ///
+    /// assert_eq!(slab[one], 1);
+    /// ```
+    #[inline]
+    pub fn insert(&mut self, obj: T) -> usize {
+        let cur;
+        if self.free != Self::NULL {
+            cur = self.free;
+            self.free = unsafe { self.slot_unchecked_mut(cur).put(obj) };
+        } else {
+            cur = self.init;
+            let page = cur / PAGE;
+            if page == self.pages.len() {
+                self.pages.push(Vec::with_capacity(PAGE));
+            }
+            // The page never grows beyond its initial capacity, so pushing
+            // does not move the other objects in the page.
+            let page = unsafe { self.pages.get_unchecked_mut(page) };
+            debug_assert!(page.len() < PAGE);
+            page.push(Slot::Used(obj));
+            self.init += 1;
+        }
+        self.len += 1;
+        cur
+    }
+
+    /// Returns a reference to the object at the specified `index` if the
+    /// object exists. Otherwise, `None` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::PagedSlab;
+    /// let mut slab: PagedSlab<_> = PagedSlab::new();
+    /// let one = slab.insert(1);
+    ///
+    /// assert_eq!(slab.get(one), Some(&1));
+    /// assert_eq!(slab.get(1), None);
+    /// ```
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if let Some(Slot::Used(obj)) = self.slot(index) {
+            return Some(obj);
+        }
+        None
+    }
+
+    /// Inserts an object to the slab, returning its index along with a pinned
+    /// mutable reference to it. The object will not move in memory until it
+    /// is dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::PagedSlab;
+    /// use std::marker::PhantomPinned;
+    ///
+    /// let mut slab: PagedSlab<_> = PagedSlab::new();
+    /// let (index, obj) = slab.insert_pinned((1, PhantomPinned));
+    ///
+    /// assert_eq!(obj.0, 1);
+    /// assert!(slab.remove_in_place(index));
+    /// ```
+    #[inline]
+    pub fn insert_pinned(&mut self, obj: T) -> (usize, Pin<&mut T>) {
+        let index = self.insert(obj);
+        let obj = unsafe { self.slot_unchecked_mut(index).get_unchecked_mut() };
+        (index, unsafe { Pin::new_unchecked(obj) })
+    }
+
+    /// Returns a pinned mutable reference to the object at the specified
+    /// `index` if the object exists. Otherwise, `None` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::PagedSlab;
+    /// use std::marker::PhantomPinned;
+    /// use std::pin::Pin;
+    ///
+    /// struct Counter(i32, PhantomPinned);
+    ///
+    /// impl Counter {
+    ///     fn incr(self: Pin<&mut Self>) {
+    ///         unsafe { self.get_unchecked_mut().0 += 1 }
+    ///     }
+    /// }
+    ///
+    /// let mut slab: PagedSlab<_> = PagedSlab::new();
+    /// let (index, _) = slab.insert_pinned(Counter(1, PhantomPinned));
+    /// slab.get_pin_mut(index).unwrap().incr();
+    ///
+    /// assert_eq!(slab[index].0, 2);
+    /// assert!(slab.get_pin_mut(1).is_none());
+    /// ```
+    #[inline]
+    pub fn get_pin_mut(&mut self, index: usize) -> Option<Pin<&mut T>> {
+        if let Some(Slot::Used(obj)) = self.slot_mut(index) {
+            return Some(unsafe { Pin::new_unchecked(obj) });
+        }
+        None
+    }
+
+    /// Drops the object at the specified `index` in place, and the slot will
+    /// be put to the list of free slots for reusing. Returns `false` if no
+    /// object is found at the specified `index`.
+    ///
+    /// Unlike [`remove`], this method is available for objects which are not
+    /// `Unpin`, since the object is never moved out of the slab.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::PagedSlab;
+    /// let mut slab: PagedSlab<_> = PagedSlab::new();
+    /// let one = slab.insert(1);
+    ///
+    /// assert!(slab.remove_in_place(one));
+    /// assert!(!slab.remove_in_place(one));
+    /// assert!(slab.is_empty());
+    /// ```
+    ///
+    /// [`remove`]: #method.remove
+    pub fn remove_in_place(&mut self, index: usize) -> bool {
+        let free = self.free;
+        let slot = match self.slot_mut(index) {
+            Some(slot @ Slot::Used(_)) => slot as *mut Slot<T>,
+            _ => return false,
+        };
+        self.free = index;
+        self.len -= 1;
+
+        // Frees the slot even if dropping the object panics.
+        struct FreeOnDrop<T>(*mut Slot<T>, usize);
+
+        impl<T> Drop for FreeOnDrop<T> {
+            #[inline]
+            fn drop(&mut self) {
+                unsafe { ptr::write(self.0, Slot::Free(self.1)) }
+            }
+        }
+
+        let guard = FreeOnDrop(slot, free);
+        unsafe { ptr::drop_in_place((*slot).get_unchecked_mut()) };
+        drop(guard);
+        true
+    }
+
+    #[inline]
+    fn slot(&self, index: usize) -> Option<&Slot<T>> {
+        self.pages.get(index / PAGE)?.get(index % PAGE)
+    }
+
+    #[inline]
+    fn slot_mut(&mut self, index: usize) -> Option<&mut Slot<T>> {
+        self.pages.get_mut(index / PAGE)?.get_mut(index % PAGE)
+    }
+
+    #[inline]
+    unsafe fn slot_unchecked_mut(&mut self, index: usize) -> &mut Slot<T> {
+        self.pages
+            .get_unchecked_mut(index / PAGE)
+            .get_unchecked_mut(index % PAGE)
+    }
+}
+
+impl<T: Unpin, const PAGE: usize> PagedSlab<T, PAGE> {
+    /// Removes and returns the object at the specified `index`, and the slot
+    /// will be put to the list of free slots for reusing. `None` is returned
+    /// if no object is found at the specified `index`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::PagedSlab;
+    /// let mut slab: PagedSlab<_> = PagedSlab::new();
+    /// let one = slab.insert(1);
+    ///
+    /// assert_eq!(slab.remove(one), Some(1));
+    /// assert!(slab.is_empty());
+    /// ```
+    #[inline]
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        let free = self.free;
+        if let Some(slot) = self.slot_mut(index) {
+            if let Slot::Used(_) = slot {
+                let obj = unsafe { slot.take(free) };
+                self.free = index;
+                self.len -= 1;
+                return Some(obj);
+            }
+        }
+        None
+    }
+
+    /// Returns a mutable reference to the object at the specified `index`
+    /// if the object exists. Otherwise, `None` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::PagedSlab;
+    /// let mut slab: PagedSlab<_> = PagedSlab::new();
+    /// let one = slab.insert(1);
+    ///
+    /// *slab.get_mut(one).unwrap() = 2;
+    ///
+    /// assert_eq!(slab[one], 2);
+    /// ```
+    #[inline]
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if let Some(Slot::Used(obj)) = self.slot_mut(index) {
+            return Some(obj);
+        }
+        None
+    }
+}
+
+impl<T, const PAGE: usize> Default for PagedSlab<T, PAGE> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const PAGE: usize> Index<usize> for PagedSlab<T, PAGE> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, index: usize) -> &Self::Output {
+        match self.get(index) {
+            Some(obj) => obj,
+            None => panic!("invalid slab index {}", index),
+        }
+    }
+}
+
+impl<T: Unpin, const PAGE: usize> IndexMut<usize> for PagedSlab<T, PAGE> {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        match self.get_mut(index) {
+            Some(obj) => obj,
+            None => panic!("invalid slab index {}", index),
+        }
+    }
+}
@@ -0,0 +1,497 @@
+//! Provides [`Pool<T>`], a slab allocator reusing its objects instead of
+//! dropping them, and the [`Reset`] trait.
+//!
+//! [`Pool<T>`]: struct.Pool.html
+//! [`Reset`]: trait.Reset.html
+
+#[cfg(not(feature = "std"))]
+use alloc::{boxed::Box, collections::VecDeque, string::String, vec::Vec};
+
+#[cfg(feature = "std")]
+use std::{boxed::Box, collections::VecDeque, string::String, vec::Vec};
+
+use core::fmt;
+use core::ops::{Index, IndexMut};
+
+use crate::Slab;
+
+/// A type whose objects can be reset to a reusable state, while keeping the
+/// resources they hold such as allocated buffers.
+///
+/// # Examples
+///
+/// ```
+/// # use ruyi_slab::Reset;
+/// let mut buf = Vec::with_capacity(16);
+/// buf.push(1);
+/// buf.reset();
+///
+/// assert!(buf.is_empty());
+/// assert!(buf.capacity() >= 16);
+/// ```
+pub trait Reset {
+    /// Resets the object.
+    fn reset(&mut self);
+}
+
+impl<T> Reset for Vec<T> {
+    #[inline]
+    fn reset(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T> Reset for VecDeque<T> {
+    #[inline]
+    fn reset(&mut self) {
+        self.clear();
+    }
+}
+
+impl Reset for String {
+    #[inline]
+    fn reset(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T: Reset + ?Sized> Reset for Box<T> {
+    #[inline]
+    fn reset(&mut self) {
+        (**self).reset();
+    }
+}
+
+/// An object based allocator like [`Slab<T>`], but keeping released objects
+/// for reusing instead of dropping them.
+///
+/// The acquired objects live in a [`Slab<T>`], which hands out their
+/// indices. Released objects are reset, either with [`Reset`] or with the
+/// closure given to [`with_reset`], and handed out again by [`acquire`],
+/// most recently released first. This avoids reallocating the resources of
+/// objects like buffers over and over.
+///
+/// The released objects are kept until they are acquired again, so after a
+/// peak of acquired objects, the pool holds as many objects as at the peak.
+/// Call [`truncate_released`] or [`clear_released`] to drop the objects
+/// which are not needed anymore, and [`shrink_to_fit`] to release the
+/// memory of the pool itself.
+///
+/// # Examples
+/// ```
+/// # use ruyi_slab::Pool;
+/// let mut pool: Pool<Vec<u8>> = Pool::new();
+/// let (index, buf) = pool.acquire();
+/// buf.extend_from_slice(b"hello");
+/// pool.release(index);
+///
+/// // The buffer is reused, with its capacity kept
+/// let (reused, buf) = pool.acquire();
+///
+/// assert_eq!(reused, index);
+/// assert!(buf.is_empty());
+/// assert!(buf.capacity() >= 5);
+/// ```
+///
+/// [`Slab<T>`]: struct.Slab.html
+/// [`Reset`]: trait.Reset.html
+/// [`with_reset`]: #method.with_reset
+/// [`acquire`]: #method.acquire
+/// [`truncate_released`]: #method.truncate_released
+/// [`clear_released`]: #method.clear_released
+/// [`shrink_to_fit`]: #method.shrink_to_fit
+pub struct Pool<T, R = fn(&mut T)> {
+    slab: Slab<T>,
+    released: Vec<T>,
+    reset: R,
+}
+
+impl<T: Reset> Pool<T> {
+    /// Constructs a new empty `Pool<T>` resetting released objects with
+    /// [`Reset`].
+    /// The allocator will not allocate until the first object is acquired.
+    ///
+    /// [`Reset`]: trait.Reset.html
+    ///
+    /// # Examples
+    /// ```
+    /// # use ruyi_slab::Pool;
+    /// let mut pool: Pool<String> = Pool::new();
+    /// # pool.acquire();
+    /// ```
+    #[inline]
+    pub const fn new() -> Self {
+        Self::with_reset(T::reset)
+    }
+
+    /// Constructs a new, empty `Pool<T>` with the specified capacity,
+    /// resetting released objects with [`Reset`].
+    ///
+    /// The pool will be able to hold exactly `capacity` acquired objects
+    /// without reallocating. If `capacity` is 0, the pool will not allocate.
+    ///
+    /// [`Reset`]: trait.Reset.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Pool;
+    /// let pool: Pool<String> = Pool::with_capacity(10);
+    ///
+    /// assert_eq!(pool.len(), 0);
+    /// assert_eq!(pool.capacity(), 10);
+    /// ```
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_reset(capacity, T::reset)
+    }
+}
+
+impl<T, R: FnMut(&mut T)> Pool<T, R> {
+    /// Constructs a new empty `Pool<T, R>` resetting released objects by
+    /// calling `reset`.
+    /// The allocator will not allocate until the first object is acquired.
+    ///
+    /// # Examples
+    /// ```
+    /// # use ruyi_slab::Pool;
+    /// let mut pool = Pool::with_reset(|buf: &mut Vec<u8>| buf.truncate(0));
+    /// let (index, buf) = pool.acquire();
+    /// buf.push(1);
+    /// pool.release(index);
+    ///
+    /// assert!(pool.acquire().1.is_empty());
+    /// ```
+    #[inline]
+    pub const fn with_reset(reset: R) -> Self {
+        Self {
+            slab: Slab::new(),
+            released: Vec::new(),
+            reset,
+        }
+    }
+
+    /// Constructs a new, empty `Pool<T, R>` with the specified capacity,
+    /// resetting released objects by calling `reset`.
+    ///
+    /// The pool will be able to hold exactly `capacity` acquired objects
+    /// without reallocating. If `capacity` is 0, the pool will not allocate.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Pool;
+    /// let pool = Pool::with_capacity_and_reset(10, String::clear);
+    ///
+    /// assert_eq!(pool.capacity(), 10);
+    /// ```
+    #[inline]
+    pub fn with_capacity_and_reset(capacity: usize, reset: R) -> Self {
+        Self {
+            slab: Slab::with_capacity(capacity),
+            released: Vec::new(),
+            reset,
+        }
+    }
+
+    /// Releases the acquired object at the specified `index` back to the
+    /// pool. The object is reset and kept in the pool for reusing. Returns
+    /// `false` if no acquired object is found at the specified `index`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Pool;
+    /// let mut pool: Pool<String> = Pool::new();
+    /// let (one, _) = pool.acquire();
+    ///
+    /// assert!(pool.release(one));
+    /// assert!(!pool.release(one));
+    /// assert!(pool.is_empty());
+    /// ```
+    pub fn release(&mut self, index: usize) -> bool {
+        match self.slab.remove(index) {
+            Some(mut obj) => {
+                (self.reset)(&mut obj);
+                self.released.push(obj);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Releases all the acquired objects back to the pool.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Pool;
+    /// let mut pool: Pool<String> = Pool::new();
+    /// pool.acquire();
+    /// pool.clear();
+    ///
+    /// assert!(pool.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        self.released.reserve(self.slab.len());
+        for (_, mut obj) in self.slab.drain() {
+            (self.reset)(&mut obj);
+            self.released.push(obj);
+        }
+    }
+}
+
+impl<T, R> Pool<T, R> {
+    /// Returns the number of acquired objects in the pool.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Pool;
+    /// let mut pool: Pool<String> = Pool::new();
+    /// let (one, _) = pool.acquire();
+    /// pool.acquire();
+    /// pool.release(one);
+    ///
+    /// assert_eq!(pool.len(), 1);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.slab.len()
+    }
+
+    /// Returns the number of acquired objects the pool can hold without
+    /// reallocating.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Pool;
+    /// let pool: Pool<String> = Pool::with_capacity(10);
+    ///
+    /// assert_eq!(pool.capacity(), 10);
+    /// ```
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.slab.capacity()
+    }
+
+    /// Returns `true` if no object is acquired from the pool.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Pool;
+    /// let mut pool: Pool<String> = Pool::new();
+    ///
+    /// assert!(pool.is_empty());
+    ///
+    /// pool.acquire();
+    ///
+    /// assert!(!pool.is_empty());
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of released objects kept in the pool for reusing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Pool;
+    /// let mut pool: Pool<String> = Pool::new();
+    /// let (one, _) = pool.acquire();
+    /// pool.release(one);
+    ///
+    /// assert_eq!(pool.len(), 0);
+    /// assert_eq!(pool.released_len(), 1);
+    /// ```
+    #[inline]
+    pub fn released_len(&self) -> usize {
+        self.released.len()
+    }
+
+    /// Drops the released objects beyond the first `len` ones kept for
+    /// reusing, starting from the least recently released. Does nothing if
+    /// no more than `len` objects are kept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Pool;
+    /// let mut pool: Pool<String> = Pool::new();
+    /// for _ in 0..4 {
+    ///     pool.acquire();
+    /// }
+    /// pool.clear();
+    /// pool.truncate_released(1);
+    ///
+    /// assert_eq!(pool.released_len(), 1);
+    /// ```
+    pub fn truncate_released(&mut self, len: usize) {
+        if let Some(excess) = self.released.len().checked_sub(len) {
+            self.released.drain(..excess);
+        }
+    }
+
+    /// Drops all the released objects kept for reusing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Pool;
+    /// let mut pool: Pool<String> = Pool::new();
+    /// let (one, _) = pool.acquire();
+    /// pool.release(one);
+    /// pool.clear_released();
+    ///
+    /// assert_eq!(pool.released_len(), 0);
+    /// ```
+    #[inline]
+    pub fn clear_released(&mut self) {
+        self.released.clear();
+    }
+
+    /// Shrinks the memory of the pool as much as possible, for both the
+    /// acquired objects and the released ones. The released objects are
+    /// kept, see [`truncate_released`] for dropping them first.
+    ///
+    /// [`truncate_released`]: #method.truncate_released
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Pool;
+    /// let mut pool: Pool<String> = Pool::with_capacity(10);
+    /// pool.acquire();
+    /// pool.shrink_to_fit();
+    ///
+    /// assert!(pool.capacity() >= 1);
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        self.slab.shrink_to_fit();
+        self.released.shrink_to_fit();
+    }
+
+    /// Acquires an object from the pool, returning its index along with a
+    /// mutable reference to it. A released object is reused if any,
+    /// otherwise a new one is created by `T::default()`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Pool;
+    /// let mut pool: Pool<String> = Pool::new();
+    /// let (index, s) = pool.acquire();
+    /// s.push_str("hello");
+    ///
+    /// assert_eq!(pool[index], "hello");
+    /// ```
+    #[inline]
+    pub fn acquire(&mut self) -> (usize, &mut T)
+    where
+        T: Default,
+    {
+        self.acquire_with(T::default)
+    }
+
+    /// Acquires an object from the pool, returning its index along with a
+    /// mutable reference to it. A released object is reused if any,
+    /// otherwise a new one is created by calling `f`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Pool;
+    /// let mut pool = Pool::new();
+    /// let (index, buf) = pool.acquire_with(|| Vec::<u8>::with_capacity(4096));
+    ///
+    /// assert!(buf.capacity() >= 4096);
+    /// ```
+    pub fn acquire_with<F>(&mut self, f: F) -> (usize, &mut T)
+    where
+        F: FnOnce() -> T,
+    {
+        let obj = self.released.pop().unwrap_or_else(f);
+        let index = self.slab.insert(obj);
+        (index, unsafe { self.slab.get_unchecked_mut(index) })
+    }
+
+    /// Returns a reference to the acquired object at the specified `index`.
+    /// `None` is returned if no acquired object is found at the specified
+    /// `index`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Pool;
+    /// let mut pool: Pool<String> = Pool::new();
+    /// let (one, _) = pool.acquire();
+    ///
+    /// assert_eq!(pool.get(one).map(String::as_str), Some(""));
+    /// assert_eq!(pool.get(1), None);
+    /// ```
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.slab.get(index)
+    }
+
+    /// Returns a mutable reference to the acquired object at the specified
+    /// `index`. `None` is returned if no acquired object is found at the
+    /// specified `index`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Pool;
+    /// let mut pool: Pool<String> = Pool::new();
+    /// let (one, _) = pool.acquire();
+    /// pool.get_mut(one).unwrap().push('a');
+    ///
+    /// assert_eq!(pool[one], "a");
+    /// ```
+    #[inline]
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.slab.get_mut(index)
+    }
+}
+
+impl<T: Reset> Default for Pool<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, R> Index<usize> for Pool<T, R> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, index: usize) -> &Self::Output {
+        match self.get(index) {
+            Some(obj) => obj,
+            None => panic!("invalid pool index {}", index),
+        }
+    }
+}
+
+impl<T, R> IndexMut<usize> for Pool<T, R> {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        match self.get_mut(index) {
+            Some(obj) => obj,
+            None => panic!("invalid pool index {}", index),
+        }
+    }
+}
+
+impl<T: fmt::Debug, R> fmt::Debug for Pool<T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pool")
+            .field("slab", &self.slab)
+            .field("released", &self.released)
+            .finish_non_exhaustive()
+    }
+}
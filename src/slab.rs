@@ -0,0 +1,2424 @@
+//! Provides [`Slab<T>`], an object based allocator backed by a contiguous
+//! growable array of slots.
+//!
+//! [`Slab<T>`]: struct.Slab.html
+
+use allocator_api2::alloc::{Allocator, Global};
+use allocator_api2::collections::TryReserveError;
+use allocator_api2::vec::{self, Vec};
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec as StdVec;
+
+#[cfg(feature = "std")]
+use std::vec::Vec as StdVec;
+
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::iter::{Enumerate, FromIterator, FusedIterator};
+use core::mem::{self, ManuallyDrop};
+use core::ops::{Index, IndexMut};
+use core::ptr;
+
+use crate::{Iter, IterMut, Key, SlabGuard, Slot};
+
+/// An object based allocator backed by a contiguous growable array of slots.
+///
+/// # Examples
+/// ```
+/// # use ruyi_slab::Slab;
+/// let mut slab = Slab::new();
+/// let one = slab.insert(1);
+/// let two = slab.insert(2);
+///
+/// assert_eq!(slab.len(), 2);
+/// assert_eq!(slab[two], 2);
+///
+/// slab.remove(one);
+///
+/// assert_eq!(slab.len(), 1);
+///
+/// let entry = slab.free_entry();
+/// let index = entry.index();
+/// entry.insert(index);
+///
+/// assert_eq!(slab.len(), 2);
+/// assert_eq!(slab[index], index);
+/// ```
+///
+/// # Capacity and reallocation
+///
+/// The capacity of a slab is the amount of space allocated for any future
+/// objects that will be inserted to the slab. This is not to be confused with
+/// the *length* of a slab, which specifies the number of actual objects
+/// within the slab. If a slab's length exceeds its capacity, its capacity
+/// will automatically be increased, but its objects will have to be
+/// reallocated.
+///
+/// For example, a slab with capacity 10 and length 0 would be an empty slab
+/// with space for 10 more objects. Inserting 10 or fewer objects into the
+/// slab will not change its capacity or cause reallocation to occur. However,
+/// if the slab's length is increased to 11, it will have to reallocate, which
+/// can be slow. For this reason, it is recommended to use [`Slab::with_capacity`]
+/// whenever possible to specify how big the slab is expected to get.
+///
+/// [`Slab::with_capacity`]: #method.with_capacity
+///
+/// # Typed keys
+///
+/// By default objects are referred to by plain `usize` indices. A slab can
+/// hand out keys of any type implementing [`Key`] instead, so that keys of
+/// different slabs cannot be mixed up. See [`slab_key!`] for declaring such
+/// key types.
+///
+/// The key type also determines the width of the links between free slots.
+/// With a narrower key such as `u32`, free slots take less memory, while the
+/// slab can hold at most [`Key::MAX`] slots.
+///
+/// [`Key`]: trait.Key.html
+/// [`Key::MAX`]: trait.Key.html#associatedconstant.MAX
+/// [`slab_key!`]: macro.slab_key.html
+///
+/// # Custom allocators
+///
+/// The slots of a slab are allocated with the allocator `A`, which defaults
+/// to the global allocator. Any allocator implementing the [`Allocator`]
+/// trait of the `allocator-api2` crate can be used instead, see
+/// [`Slab::new_in`]. With the `nightly` feature enabled, the trait is the
+/// unstable `core::alloc::Allocator` itself.
+///
+/// [`Allocator`]: https://docs.rs/allocator-api2/0.2/allocator_api2/alloc/trait.Allocator.html
+/// [`Slab::new_in`]: #method.new_in
+#[derive(Debug)]
+pub struct Slab<T, K = usize, A: Allocator = Global> {
+    slots: Vec<Slot<T, K>, A>,
+    len: usize,
+    reserved: usize,
+    free: usize,
+}
+
+unsafe impl<T: Send, K: Send, A: Allocator + Send> Send for Slab<T, K, A> {}
+
+impl<T> Slab<T> {
+    /// Constructs a new empty `Slab<T>`.
+    /// The allocator will not allocate until the first object is inserted.
+    ///
+    /// # Examples
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab = Slab::new();
+    /// # slab.insert(1);
+    /// ```
+    #[inline]
+    pub const fn new() -> Self {
+        Self::with_key()
+    }
+
+    /// Constructs a new, empty `Slab<T>` with the specified capacity.
+    ///
+    /// The slab will be able to hold exactly `capacity` objects without
+    /// reallocating. If `capacity` is 0, the slab will not allocate.
+    ///
+    /// It is important to note that although the returned slab has the
+    /// *capacity* specified, the slab will have a zero *length*. For an
+    /// explanation of the difference between length and capacity, see
+    /// *[Capacity and reallocation]*.
+    ///
+    /// [Capacity and reallocation]: #capacity-and-reallocation
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab = Slab::with_capacity(10);
+    ///
+    /// // The slab contains no objects, even though it has capacity for more
+    /// assert_eq!(slab.len(), 0);
+    ///
+    /// // These are all done without reallocating...
+    /// for i in 0..10 {
+    ///     slab.insert(i);
+    /// }
+    ///
+    /// // ...but this may make the slab reallocate
+    /// slab.insert(11);
+    /// ```
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_key(capacity)
+    }
+}
+
+impl<T, A: Allocator> Slab<T, usize, A> {
+    /// Constructs a new empty `Slab<T, usize, A>` allocating its slots with
+    /// the specified allocator.
+    /// The allocator will not allocate until the first object is inserted.
+    ///
+    /// # Examples
+    /// ```
+    /// # #![cfg_attr(feature = "nightly", feature(allocator_api))]
+    /// # use ruyi_slab::Slab;
+    /// use ruyi_slab::allocator_api2::alloc::Global;
+    ///
+    /// let mut slab = Slab::new_in(Global);
+    /// # slab.insert(1);
+    /// ```
+    #[inline]
+    pub fn new_in(alloc: A) -> Self {
+        Self::with_key_in(alloc)
+    }
+
+    /// Constructs a new, empty `Slab<T, usize, A>` with the specified
+    /// capacity, allocating its slots with the specified allocator.
+    ///
+    /// The slab will be able to hold exactly `capacity` objects without
+    /// reallocating. If `capacity` is 0, the slab will not allocate.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![cfg_attr(feature = "nightly", feature(allocator_api))]
+    /// # use ruyi_slab::Slab;
+    /// use ruyi_slab::allocator_api2::alloc::Global;
+    ///
+    /// let slab: Slab<i32, _, _> = Slab::with_capacity_in(10, Global);
+    ///
+    /// assert_eq!(slab.capacity(), 10);
+    /// ```
+    #[inline]
+    pub fn with_capacity_in(capacity: usize, alloc: A) -> Self {
+        Self::with_capacity_and_key_in(capacity, alloc)
+    }
+}
+
+impl<T, K: Key> Slab<T, K> {
+    /// Constructs a new empty `Slab<T, K>` handing out keys of type `K`.
+    /// The allocator will not allocate until the first object is inserted.
+    ///
+    /// # Examples
+    /// ```
+    /// # use ruyi_slab::{slab_key, Slab};
+    /// slab_key! {
+    ///     struct MyKey(usize);
+    /// }
+    ///
+    /// let mut slab: Slab<_, MyKey> = Slab::with_key();
+    /// # slab.insert(1);
+    /// ```
+    #[inline]
+    pub const fn with_key() -> Self {
+        Self {
+            slots: Vec::new(),
+            len: 0,
+            reserved: 0,
+            free: Self::NULL,
+        }
+    }
+
+    /// Constructs a new, empty `Slab<T, K>` handing out keys of type `K`
+    /// with the specified capacity.
+    ///
+    /// The slab will be able to hold exactly `capacity` objects without
+    /// reallocating. If `capacity` is 0, the slab will not allocate.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::{slab_key, Slab};
+    /// slab_key! {
+    ///     struct MyKey(usize);
+    /// }
+    ///
+    /// let slab: Slab<i32, MyKey> = Slab::with_capacity_and_key(10);
+    ///
+    /// assert_eq!(slab.capacity(), 10);
+    /// ```
+    #[inline]
+    pub fn with_capacity_and_key(capacity: usize) -> Self {
+        Self::with_capacity_and_key_in(capacity, Global)
+    }
+
+    /// Creates a slab holding the objects of an iterator at their specified
+    /// keys, e.g. to restore a snapshot of a slab.
+    ///
+    /// The slots at the gaps between the keys are linked to the list of free
+    /// slots in ascending order of index, so that later insertions reuse
+    /// them first.
+    ///
+    /// # Errors
+    ///
+    /// If a key occurs more than once, then an error holding the key is
+    /// returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a key is [`Key::MAX`].
+    ///
+    /// [`Key::MAX`]: trait.Key.html#associatedconstant.MAX
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab: Slab<_> = Slab::from_indexed_iter(vec![(3, 'd'), (1, 'b')]).unwrap();
+    ///
+    /// assert_eq!(slab.len(), 2);
+    /// assert_eq!(slab[3], 'd');
+    /// assert_eq!(slab.insert('a'), 0);
+    /// assert_eq!(slab.insert('c'), 2);
+    ///
+    /// let err = Slab::<_>::from_indexed_iter(vec![(1, 'a'), (1, 'b')]).unwrap_err();
+    ///
+    /// assert_eq!(err.key(), 1);
+    /// ```
+    pub fn from_indexed_iter<I>(objs: I) -> Result<Self, DuplicateKeyError<K>>
+    where
+        I: IntoIterator<Item = (K, T)>,
+    {
+        let mut slab = Self::with_key();
+        slab.place_indexed(objs)?;
+        Ok(slab)
+    }
+}
+
+impl<T, K: Key, A: Allocator> Slab<T, K, A> {
+    const NULL: usize = K::MAX;
+
+    /// Constructs a new empty `Slab<T, K, A>` handing out keys of type `K`,
+    /// and allocating its slots with the specified allocator.
+    /// The allocator will not allocate until the first object is inserted.
+    ///
+    /// # Examples
+    /// ```
+    /// # #![cfg_attr(feature = "nightly", feature(allocator_api))]
+    /// # use ruyi_slab::Slab;
+    /// use ruyi_slab::allocator_api2::alloc::Global;
+    ///
+    /// let mut slab: Slab<_, u32, _> = Slab::with_key_in(Global);
+    /// # slab.insert(1);
+    /// ```
+    #[inline]
+    pub fn with_key_in(alloc: A) -> Self {
+        Self {
+            slots: Vec::new_in(alloc),
+            len: 0,
+            reserved: 0,
+            free: Self::NULL,
+        }
+    }
+
+    /// Constructs a new, empty `Slab<T, K, A>` handing out keys of type `K`
+    /// with the specified capacity, and allocating its slots with the
+    /// specified allocator.
+    ///
+    /// The slab will be able to hold exactly `capacity` objects without
+    /// reallocating. If `capacity` is 0, the slab will not allocate.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #![cfg_attr(feature = "nightly", feature(allocator_api))]
+    /// # use ruyi_slab::Slab;
+    /// use ruyi_slab::allocator_api2::alloc::Global;
+    ///
+    /// let slab: Slab<i32, u32, _> = Slab::with_capacity_and_key_in(10, Global);
+    ///
+    /// assert_eq!(slab.capacity(), 10);
+    /// ```
+    #[inline]
+    pub fn with_capacity_and_key_in(capacity: usize, alloc: A) -> Self {
+        Self {
+            slots: Vec::with_capacity_in(capacity, alloc),
+            len: 0,
+            reserved: 0,
+            free: Self::NULL,
+        }
+    }
+
+    /// Returns a reference to the allocator of the slab.
+    #[inline]
+    pub fn allocator(&self) -> &A {
+        self.slots.allocator()
+    }
+
+    /// Returns the number of objects in the slab.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab = Slab::with_capacity(3);
+    /// slab.insert(1);
+    /// slab.insert(2);
+    /// slab.insert(3);
+    ///
+    /// assert_eq!(slab.len(), 3);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns the number of objects the slab can hold without
+    /// reallocating.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let slab: Slab<i32> = Slab::with_capacity(10);
+    ///
+    /// assert_eq!(slab.capacity(), 10);
+    /// ```
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.slots.capacity()
+    }
+
+    /// Returns `true` if the slab contains no objects.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab = Slab::new();
+    ///
+    /// assert!(slab.is_empty());
+    ///
+    /// slab.insert(1);
+    ///
+    /// assert!(!slab.is_empty());
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Clears the slab, removing all objects.
+    ///
+    /// Note that this method has no effect on the allocated capacity
+    /// of the slab.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab = Slab::with_capacity(3);
+    /// slab.insert(1);
+    /// slab.insert(2);
+    /// slab.clear();
+    ///
+    /// assert!(slab.is_empty());
+    /// ```
+    #[inline]
+    pub fn clear(&mut self) {
+        // Resets the slab before dropping the objects, so that it stays
+        // consistent even if a destructor panics.
+        let len = mem::replace(&mut self.len, 0);
+        self.reserved = 0;
+        self.free = Self::NULL;
+        if len > 0 {
+            self.slots.clear();
+        } else {
+            unsafe {
+                self.slots.set_len(0);
+            }
+        }
+    }
+
+    /// Clears the slab, returning all objects along with their indices as an
+    /// iterator, in ascending order of index.
+    ///
+    /// The slab is emptied as soon as this method returns, even if the
+    /// iterator is not fully consumed or is leaked. Objects not yet yielded
+    /// are dropped along with the iterator. Note that this method has no
+    /// effect on the allocated capacity of the slab.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab = Slab::with_capacity(3);
+    /// let one = slab.insert(1);
+    /// let two = slab.insert(2);
+    /// let three = slab.insert(3);
+    /// slab.remove(two);
+    ///
+    /// let objs: Vec<_> = slab.drain().collect();
+    ///
+    /// assert_eq!(objs, vec![(one, 1), (three, 3)]);
+    /// assert!(slab.is_empty());
+    /// assert_eq!(slab.capacity(), 3);
+    /// ```
+    #[inline]
+    pub fn drain(&mut self) -> Drain<'_, T, K, A> {
+        let len = mem::replace(&mut self.len, 0);
+        self.reserved = 0;
+        self.free = Self::NULL;
+        Drain {
+            slots: self.slots.drain(..).enumerate(),
+            len,
+        }
+    }
+
+    /// Reserves capacity for at least `additional` more objects to be inserted
+    /// in the given `Slab<T>`. The slab may reserve more space to avoid
+    /// frequent reallocations. After calling `reserve`, capacity will be
+    /// greater than or equal to `self.len() + additional`. Does nothing if
+    /// capacity is already sufficient.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new capacity overflows `usize`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab = Slab::with_capacity(1);
+    /// slab.insert(1);
+    /// slab.reserve(10);
+    ///
+    /// assert!(slab.capacity() >= 11);
+    /// ```
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        let free = self.free_len();
+        if additional > free {
+            self.slots.reserve(additional - free);
+        }
+    }
+
+    /// Reserves the minimum capacity for exactly `additional` more objects to
+    /// be inserted in the given `Slab<T>`. After calling `reserve_exact`,
+    /// capacity will be greater than or equal to `self.len() + additional`.
+    /// Does nothing if the capacity is already sufficient.
+    ///
+    /// Note that the allocator may give the collection more space than it
+    /// requests. Therefore, capacity can not be relied upon to be precisely
+    /// minimal. Prefer `reserve` if future insertions are expected.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new capacity overflows`usize`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab = Slab::with_capacity(1);
+    /// slab.insert(1);
+    /// slab.reserve_exact(10);
+    ///
+    /// assert!(slab.capacity() >= 11);
+    /// ```
+    #[inline]
+    pub fn reserve_exact(&mut self, additional: usize) {
+        let free = self.free_len();
+        if additional > free {
+            self.slots.reserve_exact(additional - free);
+        }
+    }
+
+    /// Tries to reserve capacity for at least `additional` more objects to be
+    /// inserted in the given `Slab<T>`. The slab may reserve more space to
+    /// avoid frequent reallocations. After calling `try_reserve`, capacity
+    /// will be greater than or equal to `self.len() + additional` if it
+    /// returns `Ok(())`. Does nothing if capacity is already sufficient.
+    ///
+    /// # Errors
+    ///
+    /// If the capacity overflows, or the allocator reports a failure, then an
+    /// error is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab = Slab::with_capacity(1);
+    /// slab.insert(1);
+    ///
+    /// assert!(slab.try_reserve(10).is_ok());
+    /// assert!(slab.capacity() >= 11);
+    /// assert!(slab.try_reserve(usize::MAX).is_err());
+    /// ```
+    #[inline]
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let free = self.free_len();
+        if additional > free {
+            self.slots.try_reserve(additional - free)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Tries to reserve the minimum capacity for exactly `additional` more
+    /// objects to be inserted in the given `Slab<T>`. After calling
+    /// `try_reserve_exact`, capacity will be greater than or equal to
+    /// `self.len() + additional` if it returns `Ok(())`. Does nothing if the
+    /// capacity is already sufficient.
+    ///
+    /// Note that the allocator may give the collection more space than it
+    /// requests. Therefore, capacity can not be relied upon to be precisely
+    /// minimal. Prefer `try_reserve` if future insertions are expected.
+    ///
+    /// # Errors
+    ///
+    /// If the capacity overflows, or the allocator reports a failure, then an
+    /// error is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab = Slab::with_capacity(1);
+    /// slab.insert(1);
+    ///
+    /// assert!(slab.try_reserve_exact(10).is_ok());
+    /// assert!(slab.capacity() >= 11);
+    /// ```
+    #[inline]
+    pub fn try_reserve_exact(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let free = self.free_len();
+        if additional > free {
+            self.slots.try_reserve_exact(additional - free)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Shrinks the capacity of the slab as much as possible.
+    ///
+    /// Free slots at the end of the slab are released, while the indices of
+    /// the objects remain unchanged. The capacity of the slab may still be
+    /// greater than its length because of free slots in between objects, or
+    /// because the allocator keeps more space than requested.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab = Slab::with_capacity(10);
+    /// let one = slab.insert(1);
+    /// let two = slab.insert(2);
+    /// slab.remove(two);
+    /// slab.shrink_to_fit();
+    ///
+    /// assert!(slab.capacity() >= 1);
+    /// assert_eq!(slab[one], 1);
+    /// ```
+    #[inline]
+    pub fn shrink_to_fit(&mut self) {
+        self.shrink_to(0);
+    }
+
+    /// Shrinks the capacity of the slab with a lower bound.
+    ///
+    /// Free slots at the end of the slab are released, while the indices of
+    /// the objects remain unchanged. The capacity will remain at least as
+    /// large as both the number of remaining slots and the supplied value.
+    ///
+    /// If the current capacity is less than the lower limit, this is a no-op.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab = Slab::with_capacity(10);
+    /// slab.insert(1);
+    /// slab.insert(2);
+    /// slab.shrink_to(4);
+    ///
+    /// assert!(slab.capacity() >= 4);
+    ///
+    /// slab.shrink_to(0);
+    ///
+    /// assert!(slab.capacity() >= 2);
+    /// ```
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        self.truncate_free();
+        self.slots.shrink_to(min_capacity);
+    }
+
+    /// Compacts the slab by moving objects at the end of the slab into free
+    /// slots near the front, and then shrinks its capacity as much as
+    /// possible.
+    ///
+    /// Before an object is moved, `rekey(&mut obj, from, to)` is called with
+    /// its current and new keys so that external references to the object
+    /// can be updated. If `rekey` returns `false`, the object stays where it
+    /// is. After compaction, the list of free slots is rebuilt in ascending
+    /// order of index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab = Slab::with_capacity(4);
+    /// for i in 0..4 {
+    ///     slab.insert(i);
+    /// }
+    /// slab.remove(0);
+    /// slab.remove(1);
+    ///
+    /// let mut moves = Vec::new();
+    /// slab.compact(|_, from, to| {
+    ///     moves.push((from, to));
+    ///     true
+    /// });
+    ///
+    /// assert_eq!(moves, vec![(3, 0), (2, 1)]);
+    /// assert_eq!(slab[0], 3);
+    /// assert_eq!(slab[1], 2);
+    /// ```
+    pub fn compact<F>(&mut self, mut rekey: F)
+    where
+        F: FnMut(&mut T, K, K) -> bool,
+    {
+        let guard = CompactGuard { slab: self };
+        let slots = &mut guard.slab.slots;
+        let mut front = 0;
+        let mut back = slots.len();
+        loop {
+            // Reserved slots neither move nor are filled
+            while front < back && !matches!(slots[front], Slot::Free(_)) {
+                front += 1;
+            }
+            while back > front && !matches!(slots[back - 1], Slot::Used(_)) {
+                back -= 1;
+            }
+            if front >= back {
+                break;
+            }
+
+            back -= 1;
+            let obj = unsafe { slots.get_unchecked_mut(back).get_unchecked_mut() };
+            if rekey(obj, K::from_index(back), K::from_index(front)) {
+                let slot = mem::replace(&mut slots[back], Slot::Free(K::from_index(Self::NULL)));
+                slots[front] = slot;
+                front += 1;
+            }
+        }
+        drop(guard);
+        self.slots.shrink_to_fit();
+    }
+
+    /// Inserts an object to the slab.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slab already holds [`Key::MAX`] slots and none of them
+    /// is free.
+    ///
+    /// [`Key::MAX`]: trait.Key.html#associatedconstant.MAX
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab = Slab::with_capacity(3);
+    /// slab.insert(1);
+    /// slab.insert(2);
+    ///
+    /// assert_eq!(slab.len(), 2);
+    ///
+    /// slab.insert(3);
+    ///
+    /// assert_eq!(slab.len(), 3);
+    /// ```
+    #[inline]
+    pub fn insert(&mut self, obj: T) -> K {
+        let cur;
+        if self.has_free_slots() {
+            cur = self.free;
+            self.free = unsafe { self.slots.get_unchecked_mut(cur).put(obj) }.into_index();
+        } else {
+            cur = self.slots.len();
+            if cur == Self::NULL {
+                panic!("slab key space exhausted");
+            }
+            self.slots.push(Slot::Used(obj));
+        }
+        self.len += 1;
+        K::from_index(cur)
+    }
+
+    /// Tries to insert an object to the slab, returning the object back along
+    /// with the error if there is no free slot and the slab fails to grow.
+    ///
+    /// # Errors
+    ///
+    /// If the slab already holds [`Key::MAX`] slots, or the allocator reports
+    /// a failure, then an error is returned.
+    ///
+    /// [`Key::MAX`]: trait.Key.html#associatedconstant.MAX
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab: Slab<_, u16> = Slab::with_key();
+    /// let one = slab.try_insert(1).unwrap();
+    ///
+    /// assert_eq!(slab[one], 1);
+    ///
+    /// for i in 1..u16::MAX {
+    ///     slab.insert(i);
+    /// }
+    /// let (obj, _) = slab.try_insert(0).unwrap_err();
+    ///
+    /// assert_eq!(obj, 0);
+    /// ```
+    #[inline]
+    pub fn try_insert(&mut self, obj: T) -> Result<K, (T, TryReserveError)> {
+        if !self.has_free_slots() {
+            if self.slots.len() == Self::NULL {
+                return Err((obj, capacity_overflow()));
+            }
+            if let Err(err) = self.slots.try_reserve(1) {
+                return Err((obj, err));
+            }
+        }
+        Ok(self.insert(obj))
+    }
+
+    /// Inserts all the objects of an iterator to the slab, returning their
+    /// keys in the order of the objects.
+    ///
+    /// Capacity is reserved up front according to the size hint of the
+    /// iterator. The free slots are filled first, then the remaining objects
+    /// are appended.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slab runs out of [`Key::MAX`] slots.
+    ///
+    /// [`Key::MAX`]: trait.Key.html#associatedconstant.MAX
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab = Slab::new();
+    /// let one = slab.insert(1);
+    /// slab.insert(2);
+    /// slab.remove(one);
+    ///
+    /// let keys = slab.insert_many(vec![3, 4, 5]);
+    ///
+    /// assert_eq!(keys, [0, 2, 3]);
+    /// assert_eq!(slab[keys[2]], 5);
+    /// ```
+    pub fn insert_many<I>(&mut self, objs: I) -> StdVec<K>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let objs = objs.into_iter();
+        let (additional, _) = objs.size_hint();
+        self.reserve(additional);
+        let mut keys = StdVec::with_capacity(additional);
+        keys.extend(objs.map(|obj| self.insert(obj)));
+        keys
+    }
+
+    /// Inserts an object at the specified `key`, returning the object
+    /// previously there if any.
+    ///
+    /// If the slot is beyond the slots allocated so far, the slab is
+    /// extended with free slots up to it. Otherwise if the slot is free, it
+    /// is unlinked from the list of free slots, which takes time linear in
+    /// the number of free slots.
+    ///
+    /// # Errors
+    ///
+    /// If the slot is reserved by a [`Reservation`], the key is
+    /// [`Key::MAX`], or the allocator reports a failure, then the object is
+    /// returned back along with the error.
+    ///
+    /// [`Reservation`]: struct.Reservation.html
+    /// [`Key::MAX`]: trait.Key.html#associatedconstant.MAX
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab = Slab::new();
+    ///
+    /// assert_eq!(slab.insert_at(2, "two"), Ok(None));
+    /// assert_eq!(slab.insert_at(2, "deux"), Ok(Some("two")));
+    /// assert_eq!(slab[2], "deux");
+    /// assert_eq!(slab.len(), 1);
+    ///
+    /// // The slots before are free for reusing
+    /// assert_eq!(slab.insert("zero"), 0);
+    /// assert_eq!(slab.insert("one"), 1);
+    /// ```
+    pub fn insert_at(&mut self, key: K, obj: T) -> Result<Option<T>, (T, InsertAtError)> {
+        let index = key.into_index();
+        match self.slots.get_mut(index) {
+            Some(Slot::Used(old)) => return Ok(Some(mem::replace(old, obj))),
+            Some(Slot::Reserved) => return Err((obj, InsertAtError::SlotReserved)),
+            _ => {}
+        }
+        if index >= Self::NULL {
+            return Err((obj, InsertAtError::TryReserve(capacity_overflow())));
+        }
+        if let Some(additional) = (index + 1).checked_sub(self.slots.len()) {
+            if let Err(err) = self.slots.try_reserve(additional) {
+                return Err((obj, InsertAtError::TryReserve(err)));
+            }
+        }
+        self.unlink_free(index);
+        self.len += 1;
+        unsafe { *self.slots.get_unchecked_mut(index) = Slot::Used(obj) };
+        Ok(None)
+    }
+
+    /// Returns an entry referring to an unused slot for further manipulation.
+    /// It is useful when an object to be inserted need know its slab index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab = Slab::with_capacity(1);
+    /// let entry = slab.free_entry();
+    /// let index = entry.index();
+    /// let obj = (index, "My slab index");
+    /// entry.insert(obj);
+    ///
+    /// assert_eq!(slab[index].0, index);
+    #[inline]
+    pub fn free_entry(&mut self) -> FreeEntry<'_, T, K, A> {
+        FreeEntry::new(self)
+    }
+
+    /// Returns the entry of the slot at the specified `key` for in-place
+    /// manipulation, whether the slot holds an object or not.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slot is reserved by a [`Reservation`].
+    ///
+    /// [`Reservation`]: struct.Reservation.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::{Entry, Slab};
+    /// let mut slab = Slab::new();
+    /// let one = slab.insert(1);
+    ///
+    /// match slab.entry(one) {
+    ///     Entry::Occupied(mut entry) if *entry.get() == 1 => {
+    ///         *entry.get_mut() += 1;
+    ///     }
+    ///     Entry::Occupied(entry) => {
+    ///         entry.remove();
+    ///     }
+    ///     Entry::Vacant(_) => unreachable!(),
+    /// }
+    ///
+    /// assert_eq!(slab[one], 2);
+    ///
+    /// // Vacant entries may be out of the slots allocated so far
+    /// *slab.entry(3).or_insert(0) += 4;
+    ///
+    /// assert_eq!(slab[3], 4);
+    /// assert_eq!(slab.len(), 2);
+    /// ```
+    #[inline]
+    pub fn entry(&mut self, key: K) -> Entry<'_, T, K, A> {
+        match self.slots.get(key.into_index()) {
+            Some(Slot::Used(_)) => Entry::Occupied(OccupiedEntry { slab: self, key }),
+            Some(Slot::Reserved) => panic!("reserved slab slot {}", key.into_index()),
+            _ => Entry::Vacant(VacantEntry { slab: self, key }),
+        }
+    }
+
+    /// Reserves a slot for an object to be inserted later, returning the
+    /// reservation of the slot.
+    ///
+    /// Unlike [`free_entry`], the reservation does not borrow the slab. The
+    /// reserved slot is neither counted in [`len`] nor visible to [`get`],
+    /// and it is not handed out by other insertions until the reservation is
+    /// given back by [`fill`] or [`cancel`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slab already holds [`Key::MAX`] slots and none of them
+    /// is free.
+    ///
+    /// [`free_entry`]: #method.free_entry
+    /// [`len`]: #method.len
+    /// [`get`]: #method.get
+    /// [`fill`]: #method.fill
+    /// [`cancel`]: #method.cancel
+    /// [`Key::MAX`]: trait.Key.html#associatedconstant.MAX
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab = Slab::new();
+    /// let reservation = slab.reserve_slot();
+    /// let index = reservation.index();
+    ///
+    /// assert_eq!(slab.len(), 0);
+    /// assert_eq!(slab.get(index), None);
+    /// assert_ne!(slab.insert("other"), index);
+    ///
+    /// slab.fill(reservation, "reserved");
+    ///
+    /// assert_eq!(slab[index], "reserved");
+    /// ```
+    pub fn reserve_slot(&mut self) -> Reservation<K> {
+        let cur;
+        if self.has_free_slots() {
+            cur = self.free;
+            let slot = unsafe { self.slots.get_unchecked_mut(cur) };
+            self.free = unsafe { slot.get_free_unchecked() }.into_index();
+            *slot = Slot::Reserved;
+        } else {
+            cur = self.slots.len();
+            if cur == Self::NULL {
+                panic!("slab key space exhausted");
+            }
+            self.slots.push(Slot::Reserved);
+        }
+        self.reserved += 1;
+        Reservation {
+            key: K::from_index(cur),
+        }
+    }
+
+    /// Inserts an object into the slot of the specified `reservation`,
+    /// returning the key of the object.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slot is not reserved, e.g. the reservation belongs to
+    /// another slab, or the slab has been cleared since.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab = Slab::new();
+    /// let reservation = slab.reserve_slot();
+    /// let one = slab.fill(reservation, 1);
+    ///
+    /// assert_eq!(slab[one], 1);
+    /// assert_eq!(slab.len(), 1);
+    /// ```
+    pub fn fill(&mut self, reservation: Reservation<K>, obj: T) -> K {
+        let key = reservation.key;
+        match self.slots.get_mut(key.into_index()) {
+            Some(slot @ Slot::Reserved) => {
+                *slot = Slot::Used(obj);
+                self.reserved -= 1;
+                self.len += 1;
+                key
+            }
+            _ => panic!("invalid slab reservation {}", key.into_index()),
+        }
+    }
+
+    /// Cancels the specified `reservation`, and the slot will be put to the
+    /// list of free slots for reusing.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slot is not reserved, e.g. the reservation belongs to
+    /// another slab, or the slab has been cleared since.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab: Slab<i32> = Slab::new();
+    /// let reservation = slab.reserve_slot();
+    /// let index = reservation.index();
+    /// slab.cancel(reservation);
+    ///
+    /// assert_eq!(slab.insert(1), index);
+    /// ```
+    pub fn cancel(&mut self, reservation: Reservation<K>) {
+        let index = reservation.key.into_index();
+        match self.slots.get_mut(index) {
+            Some(slot @ Slot::Reserved) => {
+                *slot = Slot::Free(K::from_index(self.free));
+                self.free = index;
+                self.reserved -= 1;
+            }
+            _ => panic!("invalid slab reservation {}", index),
+        }
+    }
+
+    /// Inserts an object to the slab, returning a guard which removes the
+    /// object from the slab when dropped.
+    ///
+    /// This helps to not leak objects on error paths. Once the object is
+    /// fully set up, the guard is turned into the index of the object with
+    /// [`SlabGuard::into_index`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slab already holds [`Key::MAX`] slots and none of them
+    /// is free.
+    ///
+    /// [`SlabGuard::into_index`]: struct.SlabGuard.html#method.into_index
+    /// [`Key::MAX`]: trait.Key.html#associatedconstant.MAX
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab = Slab::new();
+    ///
+    /// fn open(slab: &mut Slab<Vec<u8>>, ok: bool) -> Result<usize, ()> {
+    ///     let mut guard = slab.insert_guarded(Vec::new());
+    ///     guard.push(1);
+    ///     if !ok {
+    ///         return Err(());
+    ///     }
+    ///     Ok(guard.into_index())
+    /// }
+    ///
+    /// assert!(open(&mut slab, false).is_err());
+    /// assert!(slab.is_empty());
+    ///
+    /// let index = open(&mut slab, true).unwrap();
+    ///
+    /// assert_eq!(slab[index], [1]);
+    /// ```
+    #[inline]
+    pub fn insert_guarded(&mut self, obj: T) -> SlabGuard<'_, T, K, A> {
+        let key = self.insert(obj);
+        SlabGuard::new(self, key)
+    }
+
+    /// Removes and returns the object at the specified `key`, and the slot
+    /// will be put to the list of free slots for reusing. `None` is returned
+    /// if no object is found at the specified `key`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab = Slab::with_capacity(1);
+    /// let one = slab.insert(1);
+    ///
+    /// assert_eq!(slab.len(), 1);
+    /// assert_eq!(slab.remove(one).unwrap(), 1);
+    /// assert!(slab.is_empty());
+    /// ```
+    #[inline]
+    pub fn remove(&mut self, key: K) -> Option<T> {
+        let index = key.into_index();
+        if let Some(slot) = self.slots.get_mut(index) {
+            if let Slot::Used(_) = slot {
+                let obj = unsafe { slot.take(K::from_index(self.free)) };
+                self.free = index;
+                self.len -= 1;
+                return Some(obj);
+            }
+        }
+        None
+    }
+
+    /// Retains only the objects specified by the predicate.
+    ///
+    /// In other words, removes all objects `obj` for which `f(index, &obj)`
+    /// returns `false`. The slots of the removed objects are put to the list
+    /// of free slots for reusing. This method operates in place, visiting
+    /// each object exactly once in ascending order of index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab = Slab::with_capacity(4);
+    /// for i in 1..=4 {
+    ///     slab.insert(i);
+    /// }
+    /// slab.retain(|_, &obj| obj % 2 == 0);
+    ///
+    /// assert_eq!(slab.len(), 2);
+    /// assert_eq!(slab.get(0), None);
+    /// assert_eq!(slab.get(1), Some(&2));
+    /// ```
+    #[inline]
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(K, &T) -> bool,
+    {
+        self.retain_mut(|index, obj| f(index, obj));
+    }
+
+    /// Retains only the objects specified by the predicate, passing a
+    /// mutable reference to it.
+    ///
+    /// In other words, removes all objects `obj` for which
+    /// `f(index, &mut obj)` returns `false`. The slots of the removed objects
+    /// are put to the list of free slots for reusing. This method operates in
+    /// place, visiting each object exactly once in ascending order of index.
+    ///
+    /// If the predicate panics, the objects visited so far stay removed or
+    /// retained accordingly, and the slab remains in a consistent state.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab = Slab::with_capacity(4);
+    /// for i in 1..=4 {
+    ///     slab.insert(i);
+    /// }
+    /// slab.retain_mut(|index, obj| {
+    ///     *obj += index;
+    ///     *obj < 5
+    /// });
+    ///
+    /// assert_eq!(slab.len(), 2);
+    /// assert_eq!(slab[0], 1);
+    /// assert_eq!(slab[1], 3);
+    /// ```
+    pub fn retain_mut<F>(&mut self, mut f: F)
+    where
+        F: FnMut(K, &mut T) -> bool,
+    {
+        for index in 0..self.slots.len() {
+            if let Slot::Used(obj) = unsafe { self.slots.get_unchecked_mut(index) } {
+                let key = K::from_index(index);
+                if !f(key, obj) {
+                    drop(unsafe { self.remove_unchecked(key) });
+                }
+            }
+        }
+    }
+
+    /// Returns a reference to the object at the specified `key` if the
+    /// object exists. Otherwise, `None` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab = Slab::with_capacity(3);
+    /// let one = slab.insert(1);
+    /// let two = slab.insert(2);
+    /// let three = slab.insert(3);
+    ///
+    /// assert_eq!(slab.get(one), Some(&1));
+    /// assert_eq!(slab.get(three), Some(&3));
+    /// assert_eq!(slab.get(slab.len()), None);
+    ///
+    /// slab.remove(two);
+    ///
+    /// assert_eq!(slab.get(two), None);
+    /// assert_eq!(slab.get(slab.capacity()), None);
+    /// ```
+    #[inline]
+    pub fn get(&self, key: K) -> Option<&T> {
+        if let Some(Slot::Used(obj)) = self.slots.get(key.into_index()) {
+            return Some(obj);
+        }
+        None
+    }
+
+    /// Returns a mutable reference to the object at the specified `key`
+    /// if the object exists. Otherwise, `None` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab = Slab::with_capacity(2);
+    /// let one = slab.insert(1);
+    /// let two = slab.insert(2);
+    ///
+    /// assert_eq!(slab[one], 1);
+    /// assert_eq!(slab[two], 2);
+    ///
+    /// *slab.get_mut(one).unwrap() = 3;
+    /// slab.remove(two);
+    ///
+    /// assert_eq!(slab[one], 3);
+    /// assert_eq!(slab.get_mut(two), None);
+    /// ```
+    #[inline]
+    pub fn get_mut(&mut self, key: K) -> Option<&mut T> {
+        if let Some(Slot::Used(obj)) = self.slots.get_mut(key.into_index()) {
+            return Some(obj);
+        }
+        None
+    }
+
+    /// Returns mutable references to the two objects at the specified keys
+    /// at once. `None` is returned if any of the objects does not exist, or
+    /// the keys are equal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab = Slab::new();
+    /// let one = slab.insert(1);
+    /// let two = slab.insert(2);
+    ///
+    /// let (a, b) = slab.get2_mut(one, two).unwrap();
+    /// std::mem::swap(a, b);
+    ///
+    /// assert_eq!(slab[one], 2);
+    /// assert_eq!(slab[two], 1);
+    /// assert!(slab.get2_mut(one, one).is_none());
+    /// ```
+    #[inline]
+    pub fn get2_mut(&mut self, a: K, b: K) -> Option<(&mut T, &mut T)> {
+        let [a, b] = self.get_many_mut([a, b])?;
+        Some((a, b))
+    }
+
+    /// Returns mutable references to the objects at the specified keys at
+    /// once. `None` is returned if any of the objects does not exist, or any
+    /// two of the keys are equal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab = Slab::new();
+    /// let keys = [slab.insert(1), slab.insert(2), slab.insert(3)];
+    ///
+    /// for obj in slab.get_many_mut(keys).unwrap() {
+    ///     *obj *= 10;
+    /// }
+    ///
+    /// assert_eq!(slab[keys[2]], 30);
+    /// assert!(slab.get_many_mut([keys[0], keys[1], keys[0]]).is_none());
+    /// assert!(slab.get_many_mut([keys[0], 3]).is_none());
+    /// ```
+    pub fn get_many_mut<const N: usize>(&mut self, keys: [K; N]) -> Option<[&mut T; N]> {
+        let indices = keys.map(K::into_index);
+        for (i, &index) in indices.iter().enumerate() {
+            match self.slots.get(index) {
+                Some(Slot::Used(_)) if !indices[..i].contains(&index) => {}
+                _ => return None,
+            }
+        }
+        // The indices are checked to refer to distinct objects.
+        let slots = self.slots.as_mut_ptr();
+        Some(indices.map(|index| unsafe { (*slots.add(index)).get_unchecked_mut() }))
+    }
+
+    /// Removes and returns the object at the specified `key` without
+    /// checking if the object exists or not.
+    ///
+    /// # Safety
+    ///
+    /// If the slot at the specified `key` does not have an object, the
+    /// behavior of calling this method is undefined.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab = Slab::with_capacity(1);
+    /// let one = slab.insert(1);
+    ///
+    /// assert_eq!(slab.len(), 1);
+    /// unsafe {
+    ///     assert_eq!(slab.remove_unchecked(one), 1);
+    /// }
+    /// assert!(slab.is_empty());
+    /// ```
+    #[inline]
+    pub unsafe fn remove_unchecked(&mut self, key: K) -> T {
+        let index = key.into_index();
+        let obj = self
+            .slots
+            .get_unchecked_mut(index)
+            .take(K::from_index(self.free));
+        self.free = index;
+        self.len -= 1;
+        obj
+    }
+
+    /// Returns a reference to the object at the specified `key` without
+    /// checking if the object exists or not.
+    ///
+    /// # Safety
+    ///
+    /// If the slot at the specified `key` does not have an object, the
+    /// behavior of calling this method is undefined even if the resulting
+    /// reference is not used.
+    ///
+    /// For a safe alternative see [`get`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab = Slab::with_capacity(1);
+    /// let one = slab.insert(1);
+    ///
+    /// unsafe {
+    ///     assert_eq!(slab.get_unchecked(one), &1);
+    /// }
+    /// ```
+    ///
+    /// [`get`]: #method.get
+    #[inline]
+    pub unsafe fn get_unchecked(&self, key: K) -> &T {
+        self.slots.get_unchecked(key.into_index()).get_unchecked()
+    }
+
+    /// Returns a mutable reference to the object at the specified `key`
+    /// without checking if the object exists or not.
+    ///
+    /// # Safety
+    ///
+    /// If the slot at the specified `key` does not have an object, the
+    /// behavior of calling this method is undefined even if the resulting
+    /// reference is not used.
+    ///
+    /// For a safe alternative see [`get_mut`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab = Slab::with_capacity(1);
+    /// let one = slab.insert(1);
+    ///
+    /// assert_eq!(slab[one], 1);
+    ///
+    /// unsafe {
+    ///     *slab.get_unchecked_mut(one) = 2;
+    /// }
+    ///
+    /// assert_eq!(slab[one], 2);
+    /// ```
+    ///
+    /// [`get_mut`]: #method.get_mut
+    #[inline]
+    pub unsafe fn get_unchecked_mut(&mut self, key: K) -> &mut T {
+        self.slots
+            .get_unchecked_mut(key.into_index())
+            .get_unchecked_mut()
+    }
+
+    /// Returns an iterator over the objects in the slab, along with their
+    /// indices, in ascending order of index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab = Slab::with_capacity(3);
+    /// let one = slab.insert(1);
+    /// let two = slab.insert(2);
+    /// let three = slab.insert(3);
+    /// slab.remove(two);
+    ///
+    /// let mut iter = slab.iter();
+    ///
+    /// assert_eq!(iter.next(), Some((one, &1)));
+    /// assert_eq!(iter.next(), Some((three, &3)));
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T, K> {
+        Iter::new(&self.slots, self.len)
+    }
+
+    /// Returns an iterator that allows modifying the objects in the slab,
+    /// along with their indices, in ascending order of index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab = Slab::with_capacity(2);
+    /// let one = slab.insert(1);
+    /// let two = slab.insert(2);
+    ///
+    /// for (index, obj) in slab.iter_mut() {
+    ///     *obj += index;
+    /// }
+    ///
+    /// assert_eq!(slab[one], 1);
+    /// assert_eq!(slab[two], 3);
+    /// ```
+    #[inline]
+    pub fn iter_mut(&mut self) -> IterMut<'_, T, K> {
+        IterMut::new(&mut self.slots, self.len)
+    }
+
+    /// Places the objects of an iterator at their keys into an empty slab,
+    /// then links the gaps to the list of free slots.
+    fn place_indexed<I>(&mut self, objs: I) -> Result<(), DuplicateKeyError<K>>
+    where
+        I: IntoIterator<Item = (K, T)>,
+    {
+        let objs = objs.into_iter();
+        self.slots.reserve(objs.size_hint().0);
+        for (key, obj) in objs {
+            let index = key.into_index();
+            if index >= Self::NULL {
+                panic!("slab key space exhausted");
+            }
+            if index < self.slots.len() {
+                let slot = unsafe { self.slots.get_unchecked_mut(index) };
+                if let Slot::Used(_) = slot {
+                    return Err(DuplicateKeyError { key });
+                }
+                *slot = Slot::Used(obj);
+            } else {
+                self.slots
+                    .resize_with(index, || Slot::Free(K::from_index(Self::NULL)));
+                self.slots.push(Slot::Used(obj));
+            }
+            self.len += 1;
+        }
+        self.rebuild_free();
+        Ok(())
+    }
+
+    /// Releases the free slots at the end of the slab, unlinking them from
+    /// the list of free slots while keeping the order of the others.
+    fn truncate_free(&mut self) {
+        let len = self.used_len();
+        if len == self.slots.len() {
+            return;
+        }
+
+        let mut cur = self.free;
+        let mut tail = Self::NULL;
+        self.free = Self::NULL;
+        while cur != Self::NULL {
+            let next = unsafe { self.slots.get_unchecked(cur).get_free_unchecked() }.into_index();
+            if cur < len {
+                if tail == Self::NULL {
+                    self.free = cur;
+                } else {
+                    unsafe {
+                        *self.slots.get_unchecked_mut(tail) = Slot::Free(K::from_index(cur));
+                    }
+                }
+                tail = cur;
+            }
+            cur = next;
+        }
+        if tail != Self::NULL {
+            unsafe {
+                *self.slots.get_unchecked_mut(tail) = Slot::Free(K::from_index(Self::NULL));
+            }
+        }
+        self.slots.truncate(len);
+    }
+
+    /// Releases the free slots at the end of the slab, and links the others
+    /// to the list of free slots in ascending order of index.
+    fn rebuild_free(&mut self) {
+        let len = self.used_len();
+        self.slots.truncate(len);
+        self.free = Self::NULL;
+        for (index, slot) in self.slots.iter_mut().enumerate().rev() {
+            if let Slot::Free(next) = slot {
+                *next = K::from_index(self.free);
+                self.free = index;
+            }
+        }
+    }
+
+    /// Returns the number of slots up to and including the last used or
+    /// reserved one.
+    #[inline]
+    fn used_len(&self) -> usize {
+        self.slots
+            .iter()
+            .rposition(|slot| !matches!(slot, Slot::Free(_)))
+            .map_or(0, |index| index + 1)
+    }
+
+    /// Makes the slot at the specified `index` a free slot unlinked from the
+    /// list of free slots, so that an object can be put there. The slots
+    /// are extended with free ones up to `index` as needed.
+    ///
+    /// The slot at `index` must be free or beyond the slots allocated so far.
+    fn unlink_free(&mut self, index: usize) {
+        let len = self.slots.len();
+        if index >= len {
+            if index >= Self::NULL {
+                panic!("slab key space exhausted");
+            }
+            self.slots.reserve(index + 1 - len);
+            for _ in len..=index {
+                self.slots.push(Slot::Free(K::from_index(Self::NULL)));
+            }
+            for cur in (len..index).rev() {
+                unsafe {
+                    *self.slots.get_unchecked_mut(cur) = Slot::Free(K::from_index(self.free))
+                };
+                self.free = cur;
+            }
+            return;
+        }
+
+        let next = unsafe { self.slots.get_unchecked(index).get_free_unchecked() };
+        if self.free == index {
+            self.free = next.into_index();
+            return;
+        }
+        let mut cur = self.free;
+        loop {
+            let slot = unsafe { self.slots.get_unchecked_mut(cur) };
+            let link = unsafe { slot.get_free_unchecked() }.into_index();
+            if link == index {
+                *slot = Slot::Free(next);
+                return;
+            }
+            cur = link;
+        }
+    }
+
+    #[inline]
+    fn has_free_slots(&self) -> bool {
+        self.free != Self::NULL
+    }
+
+    #[inline]
+    fn next_free(&self) -> usize {
+        if self.has_free_slots() {
+            self.free
+        } else {
+            self.slots.len()
+        }
+    }
+
+    /// Returns the number of free slots.
+    #[inline]
+    fn free_len(&self) -> usize {
+        self.slots.len() - self.len - self.reserved
+    }
+}
+
+/// Returns the error reported when the key space of a slab is exhausted.
+///
+/// `TryReserveError` cannot be constructed directly, so it is obtained from
+/// a request which always overflows.
+#[cold]
+fn capacity_overflow() -> TryReserveError {
+    match Vec::<u8>::new().try_reserve(usize::MAX) {
+        Err(err) => err,
+        Ok(()) => unreachable!(),
+    }
+}
+
+/// The error returned by [`Slab::from_indexed_iter`] when a key occurs more
+/// than once.
+///
+/// [`Slab::from_indexed_iter`]: struct.Slab.html#method.from_indexed_iter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateKeyError<K = usize> {
+    key: K,
+}
+
+impl<K: Key> DuplicateKeyError<K> {
+    /// Returns the key occurring more than once.
+    #[inline]
+    pub fn key(&self) -> K {
+        self.key
+    }
+}
+
+impl<K: Key> fmt::Display for DuplicateKeyError<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "duplicate slab key {}", self.key.into_index())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: Key + fmt::Debug> std::error::Error for DuplicateKeyError<K> {}
+
+/// The error returned by [`Slab::insert_at`] when the object cannot be
+/// inserted at the key.
+///
+/// [`Slab::insert_at`]: struct.Slab.html#method.insert_at
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InsertAtError {
+    /// The slot is reserved by a [`Reservation`].
+    ///
+    /// [`Reservation`]: struct.Reservation.html
+    SlotReserved,
+    /// The slab cannot be extended up to the slot.
+    TryReserve(TryReserveError),
+}
+
+impl fmt::Display for InsertAtError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InsertAtError::SlotReserved => f.write_str("slab slot is reserved"),
+            InsertAtError::TryReserve(err) => fmt::Display::fmt(err, f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InsertAtError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            InsertAtError::SlotReserved => None,
+            InsertAtError::TryReserve(err) => Some(err),
+        }
+    }
+}
+
+/// Restores the list of free slots once a compaction is done, or has been
+/// interrupted by a panicking callback.
+struct CompactGuard<'a, T, K: Key, A: Allocator> {
+    slab: &'a mut Slab<T, K, A>,
+}
+
+impl<T, K: Key, A: Allocator> Drop for CompactGuard<'_, T, K, A> {
+    #[inline]
+    fn drop(&mut self) {
+        self.slab.rebuild_free();
+    }
+}
+
+impl<T, K: Key, A: Allocator + Default> Default for Slab<T, K, A> {
+    /// Constructs a new empty slab, like [`with_key_in`] with the default
+    /// allocator.
+    ///
+    /// Since the slab is generic over the key type and the allocator, the
+    /// type usually needs an annotation, unlike [`Slab::new`].
+    ///
+    /// [`with_key_in`]: #method.with_key_in
+    /// [`Slab::new`]: #method.new
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab: Slab<_> = Slab::default();
+    /// slab.insert(1);
+    /// ```
+    #[inline]
+    fn default() -> Self {
+        Self::with_key_in(A::default())
+    }
+}
+
+impl<T: Clone, K: Key, A: Allocator + Clone> Clone for Slab<T, K, A> {
+    /// Returns a copy of the slab, keeping the objects at the same indices
+    /// and the free slots in the same order, so that later insertions to
+    /// both slabs return the same indices.
+    ///
+    /// Slots reserved by [`reserve_slot`] stay reserved in the copy, which
+    /// has no [`Reservation`] for them, until the copy is [`clear`]ed.
+    ///
+    /// [`reserve_slot`]: #method.reserve_slot
+    /// [`Reservation`]: struct.Reservation.html
+    /// [`clear`]: #method.clear
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab: Slab<_> = (0..4).collect();
+    /// slab.remove(1);
+    /// slab.remove(2);
+    /// let mut copy = slab.clone();
+    ///
+    /// assert_eq!(copy, slab);
+    /// assert_eq!(copy.insert(4), slab.insert(4));
+    /// ```
+    fn clone(&self) -> Self {
+        Self {
+            slots: self.slots.clone(),
+            len: self.len,
+            reserved: self.reserved,
+            free: self.free,
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.slots.clone_from(&source.slots);
+        self.len = source.len;
+        self.reserved = source.reserved;
+        self.free = source.free;
+    }
+}
+
+impl<T: PartialEq, K: Key, A: Allocator> PartialEq for Slab<T, K, A> {
+    /// Returns `true` if both slabs hold equal objects at the same indices,
+    /// regardless of their free slots and capacities.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut a: Slab<_> = (0..3).collect();
+    /// a.remove(2);
+    /// let b: Slab<_> = (0..2).collect();
+    ///
+    /// assert_eq!(a, b);
+    /// ```
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len
+            && self
+                .iter()
+                .zip(other.iter())
+                .all(|((a, x), (b, y))| a.into_index() == b.into_index() && x == y)
+    }
+}
+
+impl<T: Eq, K: Key, A: Allocator> Eq for Slab<T, K, A> {}
+
+impl<T: Hash, K: Key, A: Allocator> Hash for Slab<T, K, A> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Only the objects and their indices are hashed, as compared by `eq`.
+        state.write_usize(self.len);
+        for (key, obj) in self.iter() {
+            key.into_index().hash(state);
+            obj.hash(state);
+        }
+    }
+}
+
+impl<T, K: Key, A: Allocator> Extend<T> for Slab<T, K, A> {
+    /// Inserts all the objects of an iterator to the slab, like
+    /// [`insert_many`] without collecting the keys.
+    ///
+    /// [`insert_many`]: #method.insert_many
+    fn extend<I: IntoIterator<Item = T>>(&mut self, objs: I) {
+        let objs = objs.into_iter();
+        self.reserve(objs.size_hint().0);
+        for obj in objs {
+            self.insert(obj);
+        }
+    }
+}
+
+impl<T, K: Key, A: Allocator + Default> FromIterator<T> for Slab<T, K, A> {
+    /// Creates a slab holding the objects of an iterator at consecutive keys
+    /// from 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let slab: Slab<_> = "abc".chars().collect();
+    ///
+    /// assert_eq!(slab.len(), 3);
+    /// assert_eq!(slab[2], 'c');
+    /// ```
+    fn from_iter<I: IntoIterator<Item = T>>(objs: I) -> Self {
+        let mut slab = Self::default();
+        slab.extend(objs);
+        slab
+    }
+}
+
+impl<T, K: Key, A: Allocator + Default> FromIterator<(K, T)> for Slab<T, K, A> {
+    /// Creates a slab holding the objects of an iterator at their specified
+    /// keys, like [`from_indexed_iter`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if a key occurs more than once, or is [`Key::MAX`].
+    ///
+    /// [`from_indexed_iter`]: #method.from_indexed_iter
+    /// [`Key::MAX`]: trait.Key.html#associatedconstant.MAX
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let slab: Slab<&str> = vec![(2, "two"), (0, "zero")].into_iter().collect();
+    ///
+    /// assert_eq!(slab.len(), 2);
+    /// assert_eq!(slab[2], "two");
+    /// assert_eq!(slab.get(1), None);
+    /// ```
+    fn from_iter<I: IntoIterator<Item = (K, T)>>(objs: I) -> Self {
+        let mut slab = Self::default();
+        if let Err(err) = slab.place_indexed(objs) {
+            panic!("{}", err);
+        }
+        slab
+    }
+}
+
+impl<T, K, A: Allocator> Drop for Slab<T, K, A> {
+    #[inline]
+    fn drop(&mut self) {
+        self.slots.clear();
+    }
+}
+
+impl<T, K: Key, A: Allocator> Index<K> for Slab<T, K, A> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, key: K) -> &Self::Output {
+        match self.get(key) {
+            Some(obj) => obj,
+            None => panic!("invalid slab index {}", key.into_index()),
+        }
+    }
+}
+
+impl<T, K: Key, A: Allocator> IndexMut<K> for Slab<T, K, A> {
+    #[inline]
+    fn index_mut(&mut self, key: K) -> &mut Self::Output {
+        match self.get_mut(key) {
+            Some(obj) => obj,
+            None => panic!("invalid slab index {}", key.into_index()),
+        }
+    }
+}
+
+impl<T, K: Key, A: Allocator> IntoIterator for Slab<T, K, A> {
+    type Item = (K, T);
+    type IntoIter = IntoIter<T, K, A>;
+
+    /// Creates a consuming iterator that moves the objects out of the slab,
+    /// along with their indices, in ascending order of index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab = Slab::with_capacity(2);
+    /// let one = slab.insert("one".to_string());
+    /// let two = slab.insert("two".to_string());
+    ///
+    /// let objs: Vec<_> = slab.into_iter().collect();
+    ///
+    /// assert_eq!(objs, vec![(one, "one".to_string()), (two, "two".to_string())]);
+    /// ```
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        let this = ManuallyDrop::new(self);
+        let slots = unsafe { ptr::read(&this.slots) };
+        IntoIter {
+            slots: slots.into_iter().enumerate(),
+            len: this.len,
+        }
+    }
+}
+
+impl<'a, T, K: Key, A: Allocator> IntoIterator for &'a Slab<T, K, A> {
+    type Item = (K, &'a T);
+    type IntoIter = Iter<'a, T, K>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, K: Key, A: Allocator> IntoIterator for &'a mut Slab<T, K, A> {
+    type Item = (K, &'a mut T);
+    type IntoIter = IterMut<'a, T, K>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// A handle to a free slot in a `Slab<T>`.
+///
+/// This struct is created by the [`free_entry`] method on [`Slab<T>`]. It
+/// was named `Entry` before 0.2, which now names the entry returned by
+/// [`entry`].
+///
+/// [`free_entry`]: struct.Slab.html#method.free_entry
+/// [`entry`]: struct.Slab.html#method.entry
+/// [`Slab<T>`]: struct.Slab.html
+#[derive(Debug)]
+pub struct FreeEntry<'a, T, K = usize, A: Allocator = Global> {
+    slab: &'a mut Slab<T, K, A>,
+}
+
+impl<'a, T, K: Key, A: Allocator> FreeEntry<'a, T, K, A> {
+    #[inline]
+    fn new(slab: &'a mut Slab<T, K, A>) -> Self {
+        Self { slab }
+    }
+
+    /// Returns the index of the free slot that this entry refers to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab = Slab::with_capacity(2);
+    /// slab.insert(1);
+    /// let entry = slab.free_entry();
+    /// let index = entry.index();
+    /// entry.insert(index);
+    ///
+    /// assert_eq!(slab.len(), 2);
+    /// assert_eq!(slab[index], index);
+    /// ```
+    #[inline]
+    pub fn index(&self) -> K {
+        K::from_index(self.slab.next_free())
+    }
+
+    /// Inserts the specified object into the slot this entry refers to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab = Slab::with_capacity(2);
+    /// slab.insert(1);
+    /// let entry = slab.free_entry();
+    /// let index = entry.index();
+    /// entry.insert(index);
+    ///
+    /// assert_eq!(slab.len(), 2);
+    /// assert_eq!(slab[index], index);
+    /// ```
+    #[inline]
+    pub fn insert(self, obj: T) {
+        self.slab.insert(obj);
+    }
+}
+
+/// A reservation of a slot in a `Slab<T>`, to be filled with an object later.
+///
+/// This struct is created by the [`reserve_slot`] method on [`Slab<T>`], and
+/// is given back by [`fill`] or [`cancel`]. The slot stays reserved if the
+/// reservation is dropped otherwise.
+///
+/// [`reserve_slot`]: struct.Slab.html#method.reserve_slot
+/// [`Slab<T>`]: struct.Slab.html
+/// [`fill`]: struct.Slab.html#method.fill
+/// [`cancel`]: struct.Slab.html#method.cancel
+#[derive(Debug, PartialEq, Eq)]
+#[must_use = "the slot stays reserved unless the reservation is filled or cancelled"]
+pub struct Reservation<K = usize> {
+    key: K,
+}
+
+impl<K: Key> Reservation<K> {
+    /// Returns the key of the reserved slot.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab = Slab::new();
+    /// slab.insert(1);
+    /// let reservation = slab.reserve_slot();
+    ///
+    /// assert_eq!(reservation.index(), 1);
+    /// # slab.fill(reservation, 2);
+    /// ```
+    #[inline]
+    pub fn index(&self) -> K {
+        self.key
+    }
+}
+
+/// A view into a single slot in a `Slab<T>`, which may either hold an
+/// object or not.
+///
+/// This enum is created by the [`entry`] method on [`Slab<T>`].
+///
+/// [`entry`]: struct.Slab.html#method.entry
+/// [`Slab<T>`]: struct.Slab.html
+#[derive(Debug)]
+pub enum Entry<'a, T, K = usize, A: Allocator = Global> {
+    /// A slot holding an object.
+    Occupied(OccupiedEntry<'a, T, K, A>),
+    /// A slot holding no object.
+    Vacant(VacantEntry<'a, T, K, A>),
+}
+
+impl<'a, T, K: Key, A: Allocator> Entry<'a, T, K, A> {
+    /// Returns the key of the slot this entry refers to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab: Slab<i32> = Slab::new();
+    ///
+    /// assert_eq!(slab.entry(3).index(), 3);
+    /// ```
+    #[inline]
+    pub fn index(&self) -> K {
+        match self {
+            Entry::Occupied(entry) => entry.index(),
+            Entry::Vacant(entry) => entry.index(),
+        }
+    }
+
+    /// Inserts the specified object if the slot holds no object, and
+    /// returns a mutable reference to the object in the slot.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is [`Key::MAX`].
+    ///
+    /// [`Key::MAX`]: trait.Key.html#associatedconstant.MAX
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab = Slab::new();
+    /// let one = slab.insert(1);
+    ///
+    /// assert_eq!(*slab.entry(one).or_insert(2), 1);
+    /// assert_eq!(*slab.entry(1).or_insert(2), 2);
+    /// ```
+    #[inline]
+    pub fn or_insert(self, obj: T) -> &'a mut T {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(obj),
+        }
+    }
+
+    /// Inserts the object returned by `f` if the slot holds no object, and
+    /// returns a mutable reference to the object in the slot.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is [`Key::MAX`].
+    ///
+    /// [`Key::MAX`]: trait.Key.html#associatedconstant.MAX
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab = Slab::new();
+    /// slab.entry(0).or_insert_with(Vec::new).push(1);
+    ///
+    /// assert_eq!(slab[0], [1]);
+    /// ```
+    #[inline]
+    pub fn or_insert_with<F>(self, f: F) -> &'a mut T
+    where
+        F: FnOnce() -> T,
+    {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(f()),
+        }
+    }
+
+    /// Calls `f` with the object in the slot if any, and returns the entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::Slab;
+    /// let mut slab = Slab::new();
+    /// let one = slab.insert(1);
+    /// slab.entry(one).and_modify(|obj| *obj += 1).or_insert(0);
+    ///
+    /// assert_eq!(slab[one], 2);
+    /// ```
+    #[inline]
+    pub fn and_modify<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(&mut T),
+    {
+        if let Entry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+}
+
+/// A view into a slot holding an object in a `Slab<T>`. It is part of the
+/// [`Entry`] enum.
+///
+/// [`Entry`]: enum.Entry.html
+#[derive(Debug)]
+pub struct OccupiedEntry<'a, T, K = usize, A: Allocator = Global> {
+    slab: &'a mut Slab<T, K, A>,
+    key: K,
+}
+
+impl<'a, T, K: Key, A: Allocator> OccupiedEntry<'a, T, K, A> {
+    /// Returns the key of the slot this entry refers to.
+    #[inline]
+    pub fn index(&self) -> K {
+        self.key
+    }
+
+    /// Returns a reference to the object in the slot.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::{Entry, Slab};
+    /// let mut slab = Slab::new();
+    /// let one = slab.insert(1);
+    ///
+    /// if let Entry::Occupied(entry) = slab.entry(one) {
+    ///     assert_eq!(entry.get(), &1);
+    /// }
+    /// ```
+    #[inline]
+    pub fn get(&self) -> &T {
+        unsafe { self.slab.get_unchecked(self.key) }
+    }
+
+    /// Returns a mutable reference to the object in the slot.
+    ///
+    /// To get a reference outliving the entry, use [`into_mut`].
+    ///
+    /// [`into_mut`]: #method.into_mut
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::{Entry, Slab};
+    /// let mut slab = Slab::new();
+    /// let one = slab.insert(1);
+    ///
+    /// if let Entry::Occupied(mut entry) = slab.entry(one) {
+    ///     *entry.get_mut() += 1;
+    /// }
+    ///
+    /// assert_eq!(slab[one], 2);
+    /// ```
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {
+        unsafe { self.slab.get_unchecked_mut(self.key) }
+    }
+
+    /// Converts the entry into a mutable reference to the object in the
+    /// slot, with the lifetime of the slab.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::{Entry, Slab};
+    /// let mut slab = Slab::new();
+    /// let one = slab.insert(1);
+    ///
+    /// if let Entry::Occupied(entry) = slab.entry(one) {
+    ///     *entry.into_mut() += 1;
+    /// }
+    ///
+    /// assert_eq!(slab[one], 2);
+    /// ```
+    #[inline]
+    pub fn into_mut(self) -> &'a mut T {
+        unsafe { self.slab.get_unchecked_mut(self.key) }
+    }
+
+    /// Replaces the object in the slot with the specified one, returning the
+    /// old object.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::{Entry, Slab};
+    /// let mut slab = Slab::new();
+    /// let one = slab.insert(1);
+    ///
+    /// if let Entry::Occupied(mut entry) = slab.entry(one) {
+    ///     assert_eq!(entry.insert(2), 1);
+    /// }
+    ///
+    /// assert_eq!(slab[one], 2);
+    /// ```
+    #[inline]
+    pub fn insert(&mut self, obj: T) -> T {
+        mem::replace(self.get_mut(), obj)
+    }
+
+    /// Removes the object from the slot, returning it. The slot will be put
+    /// to the list of free slots for reusing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::{Entry, Slab};
+    /// let mut slab = Slab::new();
+    /// let one = slab.insert(1);
+    ///
+    /// if let Entry::Occupied(entry) = slab.entry(one) {
+    ///     assert_eq!(entry.remove(), 1);
+    /// }
+    ///
+    /// assert!(slab.is_empty());
+    /// ```
+    #[inline]
+    pub fn remove(self) -> T {
+        unsafe { self.slab.remove_unchecked(self.key) }
+    }
+}
+
+/// A view into a slot holding no object in a `Slab<T>`. It is part of the
+/// [`Entry`] enum.
+///
+/// The slot may be beyond the slots allocated so far, in which case the
+/// slab is extended with free slots up to it on insertion.
+///
+/// [`Entry`]: enum.Entry.html
+#[derive(Debug)]
+pub struct VacantEntry<'a, T, K = usize, A: Allocator = Global> {
+    slab: &'a mut Slab<T, K, A>,
+    key: K,
+}
+
+impl<'a, T, K: Key, A: Allocator> VacantEntry<'a, T, K, A> {
+    /// Returns the key of the slot this entry refers to.
+    #[inline]
+    pub fn index(&self) -> K {
+        self.key
+    }
+
+    /// Inserts the specified object into the slot, returning a mutable
+    /// reference to it.
+    ///
+    /// Unlike inserting into a [`FreeEntry`], the slot has to be unlinked
+    /// from the list of free slots, which takes time linear in the number of
+    /// free slots.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is [`Key::MAX`].
+    ///
+    /// [`FreeEntry`]: struct.FreeEntry.html
+    /// [`Key::MAX`]: trait.Key.html#associatedconstant.MAX
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ruyi_slab::{Entry, Slab};
+    /// let mut slab = Slab::new();
+    ///
+    /// if let Entry::Vacant(entry) = slab.entry(2) {
+    ///     *entry.insert(1) += 1;
+    /// }
+    ///
+    /// assert_eq!(slab[2], 2);
+    /// assert_eq!(slab.len(), 1);
+    /// assert_eq!(slab.insert(3), 0);
+    /// ```
+    pub fn insert(self, obj: T) -> &'a mut T {
+        let index = self.key.into_index();
+        self.slab.unlink_free(index);
+        self.slab.len += 1;
+        let slot = unsafe { self.slab.slots.get_unchecked_mut(index) };
+        *slot = Slot::Used(obj);
+        unsafe { slot.get_unchecked_mut() }
+    }
+}
+
+/// An owning iterator over the objects in a `Slab<T>`.
+///
+/// This struct is created by the `into_iter` method on [`Slab<T>`]
+/// (provided by the `IntoIterator` trait). Objects not yet yielded are
+/// dropped along with the iterator.
+///
+/// [`Slab<T>`]: struct.Slab.html
+#[derive(Debug)]
+pub struct IntoIter<T, K = usize, A: Allocator = Global> {
+    slots: Enumerate<vec::IntoIter<Slot<T, K>, A>>,
+    len: usize,
+}
+
+impl<T, K: Key, A: Allocator> Iterator for IntoIter<T, K, A> {
+    type Item = (K, T);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        for (index, slot) in &mut self.slots {
+            if let Slot::Used(obj) = slot {
+                self.len -= 1;
+                return Some((K::from_index(index), obj));
+            }
+        }
+        None
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T, K: Key, A: Allocator> DoubleEndedIterator for IntoIter<T, K, A> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        while let Some((index, slot)) = self.slots.next_back() {
+            if let Slot::Used(obj) = slot {
+                self.len -= 1;
+                return Some((K::from_index(index), obj));
+            }
+        }
+        None
+    }
+}
+
+impl<T, K: Key, A: Allocator> ExactSizeIterator for IntoIter<T, K, A> {}
+
+impl<T, K: Key, A: Allocator> FusedIterator for IntoIter<T, K, A> {}
+
+/// A draining iterator over the objects in a `Slab<T>`.
+///
+/// This struct is created by the [`drain`] method on [`Slab<T>`].
+///
+/// [`drain`]: struct.Slab.html#method.drain
+/// [`Slab<T>`]: struct.Slab.html
+#[derive(Debug)]
+pub struct Drain<'a, T, K = usize, A: Allocator = Global> {
+    slots: Enumerate<vec::Drain<'a, Slot<T, K>, A>>,
+    len: usize,
+}
+
+impl<T, K: Key, A: Allocator> Iterator for Drain<'_, T, K, A> {
+    type Item = (K, T);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        for (index, slot) in &mut self.slots {
+            if let Slot::Used(obj) = slot {
+                self.len -= 1;
+                return Some((K::from_index(index), obj));
+            }
+        }
+        None
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T, K: Key, A: Allocator> DoubleEndedIterator for Drain<'_, T, K, A> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        while let Some((index, slot)) = self.slots.next_back() {
+            if let Slot::Used(obj) = slot {
+                self.len -= 1;
+                return Some((K::from_index(index), obj));
+            }
+        }
+        None
+    }
+}
+
+impl<T, K: Key, A: Allocator> ExactSizeIterator for Drain<'_, T, K, A> {}
+
+impl<T, K: Key, A: Allocator> FusedIterator for Drain<'_, T, K, A> {}
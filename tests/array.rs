@@ -0,0 +1,56 @@
+use ruyi_slab::ArraySlab;
+
+#[test]
+fn array_slab_insert_remove() {
+    let mut slab: ArraySlab<_, 3> = ArraySlab::new();
+    let a1 = slab.insert(10).unwrap();
+    let a2 = slab.insert(20).unwrap();
+    let a3 = slab.insert(30).unwrap();
+    assert!(slab.is_full());
+    assert_eq!(slab.insert(40), Err(40));
+
+    assert_eq!(slab.remove(a2), Some(20));
+    assert_eq!(slab.remove(a2), None);
+    assert_eq!(slab.len(), 2);
+
+    assert_eq!(slab.insert(50), Ok(a2));
+    assert_eq!(slab[a1], 10);
+    assert_eq!(slab[a2], 50);
+    assert_eq!(slab[a3], 30);
+    assert_eq!(slab.get(3), None);
+}
+
+#[test]
+fn array_slab_iter() {
+    let mut slab: ArraySlab<_, 4> = ArraySlab::new();
+    for i in 0..4 {
+        slab.insert(i * 10).unwrap();
+    }
+    slab.remove(1);
+
+    for (index, obj) in &mut slab {
+        *obj += index;
+    }
+    let objs: Vec<_> = slab.iter().rev().map(|(i, &v)| (i, v)).collect();
+    assert_eq!(objs, vec![(3, 33), (2, 22), (0, 0)]);
+}
+
+#[test]
+fn array_slab_drop() {
+    use std::rc::Rc;
+
+    let tracker = Rc::new(());
+    let mut slab: ArraySlab<_, 4> = ArraySlab::new();
+    slab.insert(Rc::clone(&tracker)).unwrap();
+    let a2 = slab.insert(Rc::clone(&tracker)).unwrap();
+    slab.insert(Rc::clone(&tracker)).unwrap();
+    slab.remove(a2);
+    assert_eq!(Rc::strong_count(&tracker), 3);
+
+    slab.clear();
+    assert_eq!(Rc::strong_count(&tracker), 1);
+    assert_eq!(slab.insert(Rc::clone(&tracker)), Ok(0));
+
+    drop(slab);
+    assert_eq!(Rc::strong_count(&tracker), 1);
+}
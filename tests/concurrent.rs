@@ -0,0 +1,87 @@
+#![cfg(feature = "std")]
+
+use std::sync::Arc;
+use std::thread;
+
+use ruyi_slab::ConcurrentSlab;
+
+#[test]
+fn concurrent_slab_insert_remove() {
+    let slab = ConcurrentSlab::with_shards(2);
+    let a1 = slab.insert(10);
+    let a2 = slab.insert(20);
+    assert_eq!(slab.len(), 2);
+    assert_eq!(*slab.get(a1).unwrap(), 10);
+    assert_eq!(*slab.get(a2).unwrap(), 20);
+
+    assert_eq!(slab.remove(a1), Some(10));
+    assert_eq!(slab.remove(a1), None);
+    assert!(slab.get(a1).is_none());
+    assert_eq!(slab.insert(30), a1);
+    assert!(slab.get(100).is_none());
+    assert_eq!(slab.remove(100), None);
+}
+
+#[test]
+fn concurrent_slab_threads() {
+    let slab = Arc::new(ConcurrentSlab::with_shards(4));
+
+    let handles: Vec<_> = (0..8)
+        .map(|t| {
+            let slab = slab.clone();
+            thread::spawn(move || {
+                let indices: Vec<_> = (0..1000).map(|i| (slab.insert(t * 1000 + i), i)).collect();
+                for &(index, i) in &indices {
+                    assert_eq!(*slab.get(index).unwrap(), t * 1000 + i);
+                    if i % 2 == 0 {
+                        assert_eq!(slab.remove(index), Some(t * 1000 + i));
+                    }
+                }
+                indices
+            })
+        })
+        .collect();
+
+    let mut remaining = Vec::new();
+    for handle in handles {
+        remaining.extend(
+            handle
+                .join()
+                .unwrap()
+                .into_iter()
+                .filter(|&(_, i)| i % 2 != 0),
+        );
+    }
+    assert_eq!(slab.len(), remaining.len());
+
+    // Objects inserted by one thread can be removed by another
+    let handles: Vec<_> = remaining
+        .chunks(500)
+        .map(|chunk| {
+            let slab = slab.clone();
+            let chunk = chunk.to_vec();
+            thread::spawn(move || {
+                for (index, _) in chunk {
+                    assert!(slab.remove(index).is_some());
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    assert!(slab.is_empty());
+}
+
+#[test]
+fn concurrent_slab_get_with() {
+    let slab = ConcurrentSlab::with_shards(1);
+    let a1 = slab.insert(vec![1]);
+    assert_eq!(slab.get_with(a1, Vec::len), Some(1));
+    assert_eq!(slab.get_with(a1 + 1, Vec::len), None);
+
+    // The only shard is unlocked before inserting the clone
+    let a2 = slab.insert(slab.get_with(a1, Vec::clone).unwrap());
+    assert_eq!(*slab.get(a2).unwrap(), [1]);
+    assert_eq!(slab.len(), 2);
+}
@@ -0,0 +1,120 @@
+#![cfg(feature = "alloc")]
+
+use ruyi_slab::GenSlab;
+
+#[test]
+fn gen_slab_insert_remove() {
+    let mut slab = GenSlab::new();
+    let a1 = slab.insert(10);
+    let a2 = slab.insert(20);
+    assert_eq!(slab.len(), 2);
+
+    assert_eq!(slab.remove(a1), Some(10));
+    assert_eq!(slab.remove(a1), None);
+    assert_eq!(slab.len(), 1);
+
+    let a3 = slab.insert(30);
+    assert_eq!(a3.index(), a1.index());
+    assert_ne!(a3.generation(), a1.generation());
+    assert_eq!(slab.remove(a1), None);
+    assert_eq!(slab.len(), 2);
+
+    assert_eq!(slab.remove(a2), Some(20));
+    assert_eq!(slab.remove(a3), Some(30));
+    assert!(slab.is_empty());
+}
+
+#[test]
+fn gen_slab_stale_get() {
+    let mut slab = GenSlab::new();
+    let a1 = slab.insert(10);
+    slab.remove(a1);
+    let a2 = slab.insert(20);
+
+    assert_eq!(slab.get(a1), None);
+    assert_eq!(slab.get_mut(a1), None);
+    assert!(!slab.contains(a1));
+    assert_eq!(slab[a2], 20);
+
+    slab[a2] = 200;
+    assert_eq!(slab.get(a2), Some(&200));
+}
+
+#[test]
+fn gen_slab_clear() {
+    let mut slab = GenSlab::new();
+    let a1 = slab.insert(10);
+    let a2 = slab.insert(20);
+    slab.remove(a1);
+    slab.clear();
+    assert!(slab.is_empty());
+
+    let a3 = slab.insert(30);
+    let a4 = slab.insert(40);
+    assert_eq!(slab.get(a1), None);
+    assert_eq!(slab.get(a2), None);
+    assert_eq!(slab[a3], 30);
+    assert_eq!(slab[a4], 40);
+    assert_eq!(slab.insert(50).index(), 2);
+}
+
+#[test]
+fn gen_slab_owned_guard() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let slab = Rc::new(RefCell::new(GenSlab::new()));
+    let guards: Vec<_> = (0..4).map(|i| GenSlab::insert_owned(&slab, i)).collect();
+    assert_eq!(slab.borrow().len(), 4);
+
+    *guards[1].borrow_mut() += 10;
+    assert_eq!(*guards[1].borrow(), 11);
+
+    let mut guards = guards.into_iter();
+    let a0 = guards.next().unwrap().into_index();
+    drop(guards);
+    assert_eq!(slab.borrow().len(), 1);
+    assert_eq!(slab.borrow()[a0], 0);
+    assert_eq!(Rc::strong_count(&slab), 1);
+
+    // A guard whose object was removed leaves the reused slot alone
+    let guard = GenSlab::insert_owned(&slab, 1);
+    let a1 = guard.index();
+    assert_eq!(slab.borrow_mut().remove(a1), Some(1));
+    let a2 = slab.borrow_mut().insert(2);
+    assert_eq!(a2.index(), a1.index());
+    drop(guard);
+    assert_eq!(slab.borrow()[a2], 2);
+
+    // A guard dropped while the slab is borrowed leaves its object
+    let guard = GenSlab::insert_owned(&slab, 3);
+    let a3 = guard.index();
+    {
+        let _borrowed = slab.borrow();
+        drop(guard);
+    }
+    assert_eq!(slab.borrow()[a3], 3);
+}
+
+#[test]
+fn gen_slab_owned_guard_nested() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    enum Node {
+        Leaf(i32),
+        Parent(ruyi_slab::OwnedSlabGuard<Node>),
+    }
+
+    let slab = Rc::new(RefCell::new(GenSlab::new()));
+    let leaf = GenSlab::insert_owned(&slab, Node::Leaf(1));
+    let parent = GenSlab::insert_owned(&slab, Node::Parent(leaf)).into_index();
+    assert_eq!(slab.borrow().len(), 2);
+    if let Node::Parent(leaf) = &slab.borrow()[parent] {
+        assert!(matches!(*leaf.borrow(), Node::Leaf(1)));
+    }
+
+    // Dropping the leaf guard inside clear does not panic
+    slab.borrow_mut().clear();
+    assert!(slab.borrow().is_empty());
+}
@@ -0,0 +1,56 @@
+#![cfg(all(feature = "alloc", target_has_atomic = "64", not(loom)))]
+
+use std::sync::Arc;
+use std::thread;
+
+use ruyi_slab::LockFreeSlab;
+
+#[test]
+fn lock_free_slab_insert_remove() {
+    let mut slab = LockFreeSlab::with_capacity(2);
+    let a1 = slab.insert(10).unwrap();
+    let a2 = slab.insert(20).unwrap();
+    assert_eq!(slab.len(), 2);
+    assert_eq!(slab.insert(30), Err(30));
+
+    assert_eq!(slab.remove(a1), Some(10));
+    assert_eq!(slab.remove(a1), None);
+    assert_eq!(slab.get_mut(a1), None);
+    assert_eq!(slab.get_mut(a2), Some(&mut 20));
+    assert_eq!(slab.insert(40), Ok(a1));
+    assert_eq!(slab.remove(2), None);
+
+    let empty: LockFreeSlab<i32> = LockFreeSlab::with_capacity(0);
+    assert_eq!(empty.insert(1), Err(1));
+}
+
+#[test]
+fn lock_free_slab_threads() {
+    let slab = Arc::new(LockFreeSlab::with_capacity(64));
+    let dropped = Arc::new(());
+
+    let handles: Vec<_> = (0..8)
+        .map(|_| {
+            let slab = slab.clone();
+            let dropped = dropped.clone();
+            thread::spawn(move || {
+                for _ in 0..1000 {
+                    let indices: Vec<_> = (0..8)
+                        .map(|_| slab.insert(dropped.clone()).unwrap())
+                        .collect();
+                    for index in indices {
+                        assert!(slab.remove(index).is_some());
+                    }
+                }
+                slab.insert(dropped).unwrap();
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    assert_eq!(slab.len(), 8);
+
+    drop(slab);
+    assert_eq!(Arc::strong_count(&dropped), 1);
+}
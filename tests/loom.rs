@@ -0,0 +1,79 @@
+//! Model tests of `LockFreeSlab`, run with:
+//!
+//! ```sh
+//! RUSTFLAGS="--cfg loom" cargo test --release --test loom
+//! ```
+
+#![cfg(all(loom, feature = "alloc", target_has_atomic = "64"))]
+
+use loom::sync::Arc;
+use loom::thread;
+
+use ruyi_slab::LockFreeSlab;
+
+#[test]
+fn lock_free_slab_concurrent_insert() {
+    loom::model(|| {
+        let slab = Arc::new(LockFreeSlab::with_capacity(2));
+
+        let other = slab.clone();
+        let handle = thread::spawn(move || other.insert(1).unwrap());
+        let a2 = slab.insert(2).unwrap();
+        let a1 = handle.join().unwrap();
+
+        assert_ne!(a1, a2);
+        assert_eq!(slab.insert(3), Err(3));
+        assert_eq!(slab.remove(a1), Some(1));
+        assert_eq!(slab.remove(a2), Some(2));
+    });
+}
+
+#[test]
+fn lock_free_slab_concurrent_remove() {
+    loom::model(|| {
+        let slab = Arc::new(LockFreeSlab::with_capacity(2));
+        let a1 = slab.insert(1).unwrap();
+
+        let other = slab.clone();
+        let handle = thread::spawn(move || other.remove(a1));
+        let removed = slab.remove(a1);
+        let other_removed = handle.join().unwrap();
+
+        // Only one of the threads gets the object
+        assert_eq!(removed.xor(other_removed), Some(1));
+        assert!(slab.is_empty());
+    });
+}
+
+#[test]
+fn lock_free_slab_aba() {
+    loom::model(|| {
+        let slab = Arc::new(LockFreeSlab::with_capacity(2));
+
+        // One thread pops the top slot while the other pops it, pops the
+        // next one, and pushes the first one back. Without the tag, the
+        // first thread would install the second slot as the top although it
+        // is in use.
+        let other = slab.clone();
+        let handle = thread::spawn(move || {
+            let a = other.insert(1).unwrap();
+            let b = other.insert(2);
+            assert_eq!(other.remove(a), Some(1));
+            b
+        });
+        let c = slab.insert(3);
+        let b = handle.join().unwrap();
+
+        let mut indices: Vec<_> = b.into_iter().chain(c).collect();
+        indices.sort();
+        indices.dedup();
+        assert_eq!(indices.len(), b.iter().chain(c.iter()).count());
+
+        // Every slot is accounted for
+        let mut inserted = 0;
+        while slab.insert(0).is_ok() {
+            inserted += 1;
+        }
+        assert_eq!(inserted + indices.len(), 2);
+    });
+}
@@ -0,0 +1,175 @@
+#![cfg(feature = "alloc")]
+
+use ruyi_slab::PagedSlab;
+
+#[test]
+fn paged_slab_insert_remove() {
+    let mut slab: PagedSlab<_, 2> = PagedSlab::new();
+    let a1 = slab.insert(10);
+    let a2 = slab.insert(20);
+    let a3 = slab.insert(30);
+    assert_eq!(slab.len(), 3);
+    assert_eq!(slab.capacity(), 4);
+
+    assert_eq!(slab.remove(a2), Some(20));
+    assert_eq!(slab.remove(a2), None);
+    assert_eq!(slab.insert(40), a2);
+    assert_eq!(slab[a1], 10);
+    assert_eq!(slab[a2], 40);
+    assert_eq!(slab[a3], 30);
+    assert_eq!(slab.get(3), None);
+    assert_eq!(slab.get(100), None);
+
+    slab.clear();
+    assert!(slab.is_empty());
+    assert_eq!(slab.capacity(), 4);
+    assert_eq!(slab.insert(50), 0);
+}
+
+#[test]
+fn paged_slab_stable_address() {
+    let mut slab: PagedSlab<_, 8> = PagedSlab::new();
+    let keys: Vec<_> = (0..20).map(|i| slab.insert(i)).collect();
+    let addrs: Vec<_> = keys.iter().map(|&k| &slab[k] as *const i32).collect();
+
+    for i in 0..1000 {
+        slab.insert(i);
+    }
+    slab.remove(keys[3]);
+    slab.insert(3);
+
+    for (&k, &addr) in keys.iter().zip(&addrs) {
+        assert_eq!(&slab[k] as *const i32, addr);
+    }
+}
+
+#[test]
+fn paged_slab_reserve() {
+    let mut slab: PagedSlab<_, 4> = PagedSlab::with_capacity(5);
+    assert_eq!(slab.capacity(), 8);
+
+    for i in 0..6 {
+        slab.insert(i);
+    }
+    slab.remove(0);
+    slab.reserve(3);
+    assert_eq!(slab.capacity(), 8);
+    slab.reserve(4);
+    assert_eq!(slab.capacity(), 12);
+}
+
+#[test]
+#[should_panic(expected = "capacity overflow")]
+fn paged_slab_reserve_overflow() {
+    let mut slab: PagedSlab<_, 4> = PagedSlab::new();
+    slab.insert(1);
+    slab.reserve(usize::MAX);
+}
+
+#[test]
+fn paged_slab_pinned() {
+    use std::cell::Cell;
+    use std::marker::PhantomPinned;
+    use std::pin::Pin;
+    use std::ptr;
+    use std::rc::Rc;
+
+    struct SelfRef {
+        this: *const SelfRef,
+        drops: Rc<Cell<usize>>,
+        _pin: PhantomPinned,
+    }
+
+    impl SelfRef {
+        fn init(self: Pin<&mut Self>) {
+            let this = unsafe { self.get_unchecked_mut() };
+            this.this = this;
+        }
+    }
+
+    impl Drop for SelfRef {
+        fn drop(&mut self) {
+            assert!(self.this.is_null() || ptr::eq(self.this, self));
+            self.drops.set(self.drops.get() + 1);
+        }
+    }
+
+    let drops = Rc::new(Cell::new(0));
+    let new = || SelfRef {
+        this: ptr::null(),
+        drops: drops.clone(),
+        _pin: PhantomPinned,
+    };
+
+    let mut slab: PagedSlab<_, 2> = PagedSlab::new();
+    let (a1, obj) = slab.insert_pinned(new());
+    obj.init();
+    let a2 = slab.insert(new());
+    slab.get_pin_mut(a2).unwrap().init();
+
+    for _ in 0..10 {
+        slab.insert_pinned(new()).1.init();
+    }
+    assert!(ptr::eq(slab[a1].this, &slab[a1]));
+    assert!(ptr::eq(slab[a2].this, &slab[a2]));
+
+    assert!(slab.remove_in_place(a1));
+    assert!(!slab.remove_in_place(a1));
+    assert!(slab.get_pin_mut(a1).is_none());
+    assert_eq!(drops.get(), 1);
+    assert_eq!(slab.len(), 11);
+
+    let (a3, _) = slab.insert_pinned(new());
+    assert_eq!(a3, a1);
+
+    drop(slab);
+    assert_eq!(drops.get(), 13);
+}
+
+#[test]
+fn paged_slab_clear_panic() {
+    use std::cell::Cell;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    use std::rc::Rc;
+
+    struct Tracked {
+        panics: bool,
+        drops: Rc<Cell<usize>>,
+    }
+
+    impl Drop for Tracked {
+        fn drop(&mut self) {
+            self.drops.set(self.drops.get() + 1);
+            if self.panics {
+                panic!("drop panicked");
+            }
+        }
+    }
+
+    let drops = Rc::new(Cell::new(0));
+    let new = |panics| Tracked {
+        panics,
+        drops: drops.clone(),
+    };
+
+    let mut slab: PagedSlab<_, 2> = PagedSlab::new();
+    slab.insert(new(true));
+    for _ in 0..3 {
+        slab.insert(new(false));
+    }
+    let addrs: Vec<_> = (0..4).map(|i| &slab[i] as *const Tracked).collect();
+
+    let result = catch_unwind(AssertUnwindSafe(|| slab.clear()));
+    assert!(result.is_err());
+    assert!(slab.is_empty());
+    assert_eq!(drops.get(), 4);
+
+    // The pages are reused without moving anything
+    for i in 0..4 {
+        assert_eq!(slab.insert(new(false)), i);
+    }
+    for (i, &addr) in addrs.iter().enumerate() {
+        assert_eq!(&slab[i] as *const Tracked, addr);
+    }
+    assert_eq!(slab.capacity(), 4);
+}
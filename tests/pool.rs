@@ -0,0 +1,116 @@
+#![cfg(feature = "alloc")]
+
+use ruyi_slab::{Pool, Reset};
+
+#[test]
+fn pool_acquire_release() {
+    let mut pool: Pool<Vec<u8>> = Pool::new();
+    let (a1, buf) = pool.acquire();
+    buf.extend_from_slice(b"one");
+    let (a2, buf) = pool.acquire();
+    buf.extend_from_slice(b"two");
+    assert_eq!(pool.len(), 2);
+
+    assert!(pool.release(a1));
+    assert!(!pool.release(a1));
+    assert_eq!(pool.get(a1), None);
+    assert_eq!(pool[a2], b"two");
+
+    let (a3, buf) = pool.acquire();
+    assert_eq!(a3, a1);
+    assert!(buf.is_empty());
+    assert!(buf.capacity() >= 3);
+
+    pool.clear();
+    assert!(pool.is_empty());
+    let (a4, _) = pool.acquire();
+    assert_eq!(a4, 0);
+    let (a5, _) = pool.acquire();
+    assert_eq!(a5, 1);
+    let (a6, _) = pool.acquire();
+    assert_eq!(a6, 2);
+}
+
+#[test]
+fn pool_reset() {
+    #[derive(Default)]
+    struct Conn {
+        resets: usize,
+        buf: String,
+    }
+
+    impl Reset for Conn {
+        fn reset(&mut self) {
+            self.resets += 1;
+            self.buf.clear();
+        }
+    }
+
+    let mut pool = Pool::new();
+    let (a1, conn) = pool.acquire_with(|| Conn {
+        resets: 0,
+        buf: String::with_capacity(64),
+    });
+    conn.buf.push_str("hello");
+    pool.release(a1);
+
+    // Released objects are reused instead of being created again
+    let (a2, conn) = pool.acquire_with(|| unreachable!());
+    assert_eq!(a2, a1);
+    assert_eq!(conn.resets, 1);
+    assert!(conn.buf.is_empty());
+    assert!(conn.buf.capacity() >= 64);
+
+    let (a3, conn) = pool.acquire();
+    assert_eq!(conn.resets, 0);
+    assert_ne!(a3, a2);
+}
+
+#[test]
+fn pool_with_reset() {
+    let mut pool = Pool::with_reset(|buf: &mut Vec<u8>| {
+        buf.clear();
+        buf.push(0);
+    });
+    let (a1, buf) = pool.acquire();
+    buf.extend_from_slice(b"one");
+    let (a2, _) = pool.acquire();
+    assert!(pool.release(a1));
+    assert_eq!(pool.len(), 1);
+
+    let (a3, buf) = pool.acquire_with(|| unreachable!());
+    assert_eq!(a3, a1);
+    assert_eq!(buf, &[0]);
+
+    pool.clear();
+    assert!(pool.is_empty());
+    let (_, buf) = pool.acquire();
+    assert_eq!(buf, &[0]);
+    assert_eq!(pool.get(a2), None);
+}
+
+#[test]
+fn pool_truncate_released() {
+    let mut pool: Pool<Vec<u8>> = Pool::new();
+    for i in 0..4 {
+        pool.acquire().1.reserve(i * 16 + 1);
+    }
+    pool.release(0);
+    pool.release(3);
+    assert_eq!(pool.released_len(), 2);
+
+    // The most recently released objects are kept
+    pool.truncate_released(1);
+    assert_eq!(pool.released_len(), 1);
+    let (a3, buf) = pool.acquire_with(|| unreachable!());
+    assert_eq!(a3, 3);
+    assert!(buf.capacity() >= 49);
+
+    pool.truncate_released(4);
+    pool.clear();
+    assert_eq!(pool.released_len(), 3);
+    pool.clear_released();
+    assert_eq!(pool.released_len(), 0);
+    pool.shrink_to_fit();
+    assert_eq!(pool.capacity(), 0);
+}
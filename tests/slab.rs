@@ -1,4 +1,7 @@
-use ruyi_slab::Slab;
+#![cfg(feature = "alloc")]
+#![cfg_attr(feature = "nightly", feature(allocator_api))]
+
+use ruyi_slab::{InsertAtError, Slab};
 
 #[test]
 fn slab_insert() {
@@ -75,3 +78,710 @@ fn slab_remove_unchecked() {
 
     assert_eq!(slab.len(), 1);
 }
+
+#[test]
+fn slab_iter() {
+    let mut slab = Slab::new();
+    let a1 = slab.insert(10);
+    let a2 = slab.insert(20);
+    let a3 = slab.insert(30);
+    let a4 = slab.insert(40);
+    slab.remove(a2);
+
+    let mut iter = slab.iter();
+    assert_eq!(iter.len(), 3);
+    assert_eq!(iter.next(), Some((a1, &10)));
+    assert_eq!(iter.next_back(), Some((a4, &40)));
+    assert_eq!(iter.len(), 1);
+    assert_eq!(iter.next_back(), Some((a3, &30)));
+    assert_eq!(iter.next(), None);
+    assert_eq!(iter.next_back(), None);
+
+    for (index, obj) in &mut slab {
+        *obj += index;
+    }
+    let collected: Vec<_> = (&slab).into_iter().map(|(i, &v)| (i, v)).collect();
+    assert_eq!(collected, vec![(a1, 10), (a3, 32), (a4, 43)]);
+
+    let mut empty = Slab::<i32>::new();
+    assert_eq!(empty.iter().next(), None);
+    assert_eq!(empty.iter_mut().next_back(), None);
+}
+
+#[test]
+fn slab_into_iter() {
+    use std::rc::Rc;
+
+    let tracker = Rc::new(());
+    let mut slab = Slab::new();
+    let a1 = slab.insert(Rc::clone(&tracker));
+    let a2 = slab.insert(Rc::clone(&tracker));
+    slab.insert(Rc::clone(&tracker));
+    let a4 = slab.insert(Rc::clone(&tracker));
+    slab.remove(a2);
+    assert_eq!(Rc::strong_count(&tracker), 4);
+
+    let mut iter = slab.into_iter();
+    assert_eq!(iter.len(), 3);
+    assert_eq!(iter.next().map(|(i, _)| i), Some(a1));
+    assert_eq!(iter.next_back().map(|(i, _)| i), Some(a4));
+    assert_eq!(iter.len(), 1);
+    assert_eq!(Rc::strong_count(&tracker), 2);
+
+    drop(iter);
+    assert_eq!(Rc::strong_count(&tracker), 1);
+
+    let mut slab = Slab::new();
+    slab.insert(10);
+    let a2 = slab.insert(20);
+    slab.insert(30);
+    slab.remove(a2);
+    let objs: Vec<_> = slab.into_iter().rev().collect();
+    assert_eq!(objs, vec![(2, 30), (0, 10)]);
+}
+
+#[test]
+fn slab_drain() {
+    use std::rc::Rc;
+
+    let tracker = Rc::new(());
+    let mut slab = Slab::with_capacity(4);
+    let a1 = slab.insert(Rc::clone(&tracker));
+    let a2 = slab.insert(Rc::clone(&tracker));
+    slab.insert(Rc::clone(&tracker));
+    slab.remove(a2);
+
+    let mut drain = slab.drain();
+    assert_eq!(drain.len(), 2);
+    assert_eq!(drain.next().map(|(i, _)| i), Some(a1));
+    drop(drain);
+    assert_eq!(Rc::strong_count(&tracker), 1);
+    assert!(slab.is_empty());
+    assert_eq!(slab.capacity(), 4);
+    assert_eq!(slab.insert(Rc::clone(&tracker)), 0);
+
+    slab.insert(Rc::clone(&tracker));
+    std::mem::forget(slab.drain());
+    assert!(slab.is_empty());
+    assert_eq!(slab.iter().count(), 0);
+    assert_eq!(slab.insert(Rc::clone(&tracker)), 0);
+    assert_eq!(slab.len(), 1);
+}
+
+#[test]
+fn slab_clear() {
+    let mut slab = Slab::new();
+    let a1 = slab.insert(10);
+    slab.remove(a1);
+    slab.clear();
+
+    assert_eq!(slab.insert(20), 0);
+    assert_eq!(slab.insert(30), 1);
+    assert_eq!(slab.len(), 2);
+}
+
+#[test]
+fn slab_clear_panic() {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    struct PanicOnDrop(bool);
+
+    impl Drop for PanicOnDrop {
+        fn drop(&mut self) {
+            if self.0 {
+                panic!("drop panicked");
+            }
+        }
+    }
+
+    let mut slab = Slab::new();
+    slab.insert(PanicOnDrop(false));
+    slab.insert(PanicOnDrop(true));
+    slab.insert(PanicOnDrop(false));
+    slab.remove(0);
+
+    let result = catch_unwind(AssertUnwindSafe(|| slab.clear()));
+    assert!(result.is_err());
+    assert_eq!(slab.len(), 0);
+    assert_eq!(slab.iter().count(), 0);
+    assert_eq!(slab.insert(PanicOnDrop(false)), 0);
+    assert_eq!(slab.insert(PanicOnDrop(false)), 1);
+    assert_eq!(slab.len(), 2);
+}
+
+#[test]
+fn slab_retain() {
+    let mut slab = Slab::new();
+    for i in 0..6 {
+        slab.insert(i * 10);
+    }
+    slab.remove(1);
+
+    slab.retain(|index, &obj| index != 4 && obj != 50);
+    assert_eq!(slab.len(), 3);
+    let remaining: Vec<_> = slab.iter().map(|(i, &v)| (i, v)).collect();
+    assert_eq!(remaining, vec![(0, 0), (2, 20), (3, 30)]);
+
+    // Freed slots are reused before the slab grows
+    let mut reused = vec![slab.insert(1), slab.insert(2), slab.insert(3)];
+    reused.sort_unstable();
+    assert_eq!(reused, vec![1, 4, 5]);
+    assert_eq!(slab.insert(4), 6);
+}
+
+#[test]
+fn slab_retain_mut_panic() {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    let mut slab = Slab::new();
+    for i in 0..4 {
+        slab.insert(i);
+    }
+
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        slab.retain_mut(|index, obj| {
+            if index == 2 {
+                panic!("predicate panicked");
+            }
+            *obj += 10;
+            index != 0
+        })
+    }));
+    assert!(result.is_err());
+    assert_eq!(slab.len(), 3);
+    assert_eq!(slab.get(0), None);
+    assert_eq!(slab[1], 11);
+    assert_eq!(slab[2], 2);
+    assert_eq!(slab.insert(0), 0);
+    assert_eq!(slab.insert(4), 4);
+}
+
+mod keys {
+    ruyi_slab::slab_key! {
+        pub struct ConnId(usize);
+    }
+}
+
+#[test]
+fn slab_typed_key() {
+    use keys::ConnId;
+
+    let mut slab: Slab<_, ConnId> = Slab::with_capacity_and_key(2);
+    let a1 = slab.insert(10);
+    let a2 = slab.insert(20);
+    assert_ne!(a1, a2);
+    assert_eq!(slab[a1], 10);
+
+    slab[a2] = 200;
+    assert_eq!(slab.get(a2), Some(&200));
+    assert_eq!(slab.remove(a1), Some(10));
+    assert_eq!(slab.get(a1), None);
+
+    let a3 = slab.free_entry().index();
+    assert_eq!(a3, a1);
+    assert_eq!(slab.insert(30), a3);
+
+    let objs: Vec<_> = slab.iter().collect();
+    assert_eq!(objs, vec![(a3, &30), (a2, &200)]);
+
+    let objs: Vec<_> = slab.into_iter().collect();
+    assert_eq!(objs, vec![(a3, 30), (a2, 200)]);
+}
+
+#[test]
+fn slab_narrow_key() {
+    let mut slab: Slab<u8, u16> = Slab::with_key();
+    for i in 0..u16::MAX {
+        assert_eq!(slab.insert(0), i);
+    }
+    assert_eq!(slab.len(), u16::MAX as usize);
+
+    slab.remove(7);
+    assert_eq!(slab.insert(1), 7);
+    assert_eq!(slab[7], 1);
+}
+
+#[test]
+#[should_panic(expected = "slab key space exhausted")]
+fn slab_narrow_key_exhausted() {
+    let mut slab: Slab<u8, u16> = Slab::with_key();
+    for _ in 0..=u16::MAX {
+        slab.insert(0);
+    }
+}
+
+#[test]
+fn slab_shrink_to_fit() {
+    let mut slab = Slab::with_capacity(16);
+    for i in 0..8 {
+        slab.insert(i);
+    }
+    for &i in &[1, 6, 3, 7, 5] {
+        slab.remove(i);
+    }
+    slab.shrink_to_fit();
+    assert!(slab.capacity() >= 5);
+    assert!(slab.capacity() < 16);
+    assert_eq!(slab.len(), 3);
+    assert_eq!(slab[0], 0);
+    assert_eq!(slab[2], 2);
+    assert_eq!(slab[4], 4);
+
+    // Free slots before the last object are reused in the original order
+    assert_eq!(slab.insert(10), 3);
+    assert_eq!(slab.insert(11), 1);
+    assert_eq!(slab.insert(12), 5);
+    assert_eq!(slab.insert(13), 6);
+
+    slab.clear();
+    slab.shrink_to(4);
+    assert!(slab.capacity() >= 4);
+    assert_eq!(slab.insert(1), 0);
+}
+
+#[test]
+fn slab_compact() {
+    let mut slab = Slab::with_capacity(16);
+    for i in 0..8 {
+        slab.insert(i);
+    }
+    for &i in &[0, 2, 3, 6] {
+        slab.remove(i);
+    }
+
+    // Object 7 refuses to move, so it keeps its index
+    let mut moves = Vec::new();
+    slab.compact(|&mut obj, from, to| {
+        assert_eq!(obj, from);
+        if obj == 7 {
+            return false;
+        }
+        moves.push((from, to));
+        true
+    });
+    assert_eq!(moves, vec![(5, 0), (4, 2)]);
+    assert_eq!(slab.len(), 4);
+    assert!(slab.capacity() < 16);
+    let objs: Vec<_> = slab.iter().map(|(i, &v)| (i, v)).collect();
+    assert_eq!(objs, vec![(0, 5), (1, 1), (2, 4), (7, 7)]);
+
+    assert_eq!(slab.insert(10), 3);
+    assert_eq!(slab.insert(11), 4);
+    assert_eq!(slab.insert(12), 5);
+    assert_eq!(slab.insert(13), 6);
+    assert_eq!(slab.insert(14), 8);
+}
+
+#[test]
+fn slab_compact_panic() {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    let mut slab = Slab::new();
+    for i in 0..6 {
+        slab.insert(i);
+    }
+    slab.remove(0);
+    slab.remove(1);
+
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        slab.compact(|&mut obj, _, _| {
+            if obj == 4 {
+                panic!("rekey panicked");
+            }
+            true
+        })
+    }));
+    assert!(result.is_err());
+    assert_eq!(slab.len(), 4);
+    let objs: Vec<_> = slab.iter().map(|(i, &v)| (i, v)).collect();
+    assert_eq!(objs, vec![(0, 5), (2, 2), (3, 3), (4, 4)]);
+    assert_eq!(slab.insert(6), 1);
+    assert_eq!(slab.insert(7), 5);
+}
+
+#[test]
+fn slab_reserve() {
+    let mut slab = Slab::with_capacity(10);
+    slab.insert(1);
+    slab.insert(2);
+    slab.reserve(10);
+    assert!(slab.capacity() >= 12);
+
+    let mut slab = Slab::with_capacity(10);
+    let a1 = slab.insert(1);
+    slab.insert(2);
+    slab.remove(a1);
+    slab.reserve_exact(10);
+    assert!(slab.capacity() >= 11);
+}
+
+#[test]
+fn slab_try_insert() {
+    let mut slab = Slab::new();
+    assert!(slab.try_reserve(4).is_ok());
+    assert!(slab.capacity() >= 4);
+    assert!(slab.try_reserve_exact(usize::MAX).is_err());
+
+    let a1 = slab.try_insert(10).unwrap();
+    let a2 = slab.try_insert(20).unwrap();
+    assert_eq!(slab[a1], 10);
+    assert_eq!(slab[a2], 20);
+
+    slab.remove(a1);
+    assert_eq!(slab.try_insert(30), Ok(a1));
+    assert_eq!(slab.len(), 2);
+}
+
+#[test]
+fn slab_allocator() {
+    use allocator_api2::alloc::{AllocError, Allocator, Global, Layout};
+    use std::cell::Cell;
+    use std::ptr::NonNull;
+
+    #[derive(Default)]
+    struct Counting {
+        allocated: Cell<usize>,
+    }
+
+    unsafe impl Allocator for Counting {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            self.allocated.set(self.allocated.get() + layout.size());
+            Global.allocate(layout)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            self.allocated.set(self.allocated.get() - layout.size());
+            Global.deallocate(ptr, layout)
+        }
+    }
+
+    let alloc = Counting::default();
+    let mut slab = Slab::with_capacity_in(4, &alloc);
+    assert!(alloc.allocated.get() > 0);
+
+    let a1 = slab.insert(10u64);
+    for i in 0..15 {
+        slab.insert(i);
+    }
+    assert_eq!(slab[a1], 10);
+    slab.retain(|index, _| index < 2);
+    slab.shrink_to_fit();
+    let shrunk = alloc.allocated.get();
+    assert!(shrunk > 0);
+    assert!(slab.capacity() < 16);
+
+    let objs: Vec<_> = slab.into_iter().collect();
+    assert_eq!(objs, vec![(0, 10), (1, 0)]);
+    assert_eq!(alloc.allocated.get(), 0);
+}
+
+#[test]
+fn slab_guard() {
+    let mut slab = Slab::new();
+    let a1 = slab.insert(1);
+
+    {
+        let mut guard = slab.insert_guarded(2);
+        *guard += 1;
+        assert_eq!(*guard, 3);
+    }
+    assert_eq!(slab.len(), 1);
+
+    let a2 = {
+        let mut guard = slab.insert_guarded(4);
+        *guard += 1;
+        guard.into_index()
+    };
+    assert_eq!(slab[a2], 5);
+
+    let guard = slab.insert_guarded(6);
+    let a3 = guard.index();
+    guard.forget();
+    assert_eq!(slab[a3], 6);
+    assert_eq!(slab[a1], 1);
+    assert_eq!(slab.len(), 3);
+}
+
+#[test]
+fn slab_get_many_mut() {
+    let mut slab = Slab::new();
+    let a1 = slab.insert(1);
+    let a2 = slab.insert(2);
+    let a3 = slab.insert(3);
+    slab.remove(a2);
+
+    let (x, y) = slab.get2_mut(a3, a1).unwrap();
+    *x += 10;
+    *y += 20;
+    assert_eq!(slab[a1], 21);
+    assert_eq!(slab[a3], 13);
+
+    assert!(slab.get2_mut(a1, a1).is_none());
+    assert!(slab.get2_mut(a1, a2).is_none());
+    assert!(slab.get2_mut(a1, 100).is_none());
+
+    let [x, y] = slab.get_many_mut([a1, a3]).unwrap();
+    std::mem::swap(x, y);
+    assert_eq!(slab[a1], 13);
+    assert_eq!(slab[a3], 21);
+
+    assert!(slab.get_many_mut([a3, a1, a3]).is_none());
+    assert_eq!(slab.get_many_mut::<0>([]), Some([]));
+}
+
+#[test]
+fn slab_entry() {
+    use ruyi_slab::Entry;
+
+    let mut slab = Slab::new();
+    let a1 = slab.insert(1);
+    let a2 = slab.insert(2);
+    let a3 = slab.insert(3);
+    let a4 = slab.insert(4);
+    slab.remove(a1);
+    slab.remove(a3);
+
+    match slab.entry(a2) {
+        Entry::Occupied(mut entry) => {
+            assert_eq!(entry.index(), a2);
+            assert_eq!(entry.insert(20), 2);
+            *entry.get_mut() += 1;
+            assert_eq!(*entry.get(), 21);
+        }
+        Entry::Vacant(_) => panic!("occupied slot"),
+    }
+    match slab.entry(a4) {
+        Entry::Occupied(entry) => assert_eq!(entry.remove(), 4),
+        Entry::Vacant(_) => panic!("occupied slot"),
+    }
+    assert_eq!(slab.len(), 1);
+
+    // Free slots in the middle of the list of free slots
+    assert_eq!(*slab.entry(a3).or_insert(30), 30);
+    assert_eq!(slab.len(), 2);
+
+    // Slots beyond the allocated ones
+    assert_eq!(*slab.entry(6).or_insert_with(|| 60), 60);
+    assert_eq!(slab.len(), 3);
+    slab.entry(6).and_modify(|obj| *obj += 1);
+    assert_eq!(slab[6], 61);
+    assert_eq!(slab.get(5), None);
+
+    let mut keys: Vec<_> = (0..4).map(|i| slab.insert(i)).collect();
+    keys.sort_unstable();
+    assert_eq!(keys, vec![a1, a4, 4, 5]);
+    assert_eq!(slab.insert(7), 7);
+    assert_eq!(slab.len(), 8);
+}
+
+#[test]
+fn slab_insert_at() {
+    let mut slab = Slab::new();
+    for i in 0..6 {
+        slab.insert(i);
+    }
+    for i in [4, 1, 3] {
+        slab.remove(i);
+    }
+
+    // Unlinks the head, the middle and the tail of the list of free slots
+    assert_eq!(slab.insert_at(3, 30), Ok(None));
+    assert_eq!(slab.insert_at(4, 40), Ok(None));
+    assert_eq!(slab.insert_at(0, 10), Ok(Some(0)));
+    assert_eq!(slab.len(), 5);
+    assert_eq!(slab.insert(50), 1);
+    assert_eq!(slab.insert(60), 6);
+
+    assert_eq!(slab.insert_at(9, 90), Ok(None));
+    assert_eq!(slab.len(), 8);
+    let mut keys: Vec<_> = (0..2).map(|i| slab.insert(i)).collect();
+    keys.sort_unstable();
+    assert_eq!(keys, vec![7, 8]);
+    assert_eq!(slab.insert(100), 10);
+
+    let objs: Vec<_> = slab.into_iter().map(|(_, obj)| obj).collect();
+    assert_eq!(objs, vec![10, 50, 2, 30, 40, 5, 60, 0, 1, 90, 100]);
+
+    let mut narrow: Slab<_, u16> = Slab::with_key();
+    let (obj, _) = narrow.insert_at(u16::MAX, 1).unwrap_err();
+    assert_eq!(obj, 1);
+    assert!(narrow.is_empty());
+}
+
+#[test]
+fn slab_reserve_slot() {
+    let mut slab = Slab::new();
+    let a1 = slab.insert(1);
+    let r1 = slab.reserve_slot();
+    let r2 = slab.reserve_slot();
+    assert_eq!(slab.len(), 1);
+    assert_eq!(slab.get(r1.index()), None);
+    assert_eq!(slab.iter().count(), 1);
+
+    // Reserved slots are skipped by insertions
+    let a2 = slab.insert(2);
+    assert_eq!(a2, 3);
+    slab.remove(a1);
+    assert_eq!(slab.insert(10), a1);
+
+    let i2 = r2.index();
+    slab.cancel(r2);
+    assert_eq!(slab.insert(20), i2);
+
+    // Reserved slots are kept in place by compaction
+    slab.remove(a1);
+    let r3 = slab.reserve_slot();
+    let i3 = r3.index();
+    assert_eq!(i3, a1);
+    slab.remove(i2);
+    slab.remove(a2);
+    slab.compact(|_, _, _| true);
+    assert_eq!(slab.len(), 0);
+    assert_eq!(slab.capacity(), 2);
+
+    let i1 = r1.index();
+    assert_eq!(slab.fill(r1, 30), i1);
+    assert_eq!(slab.fill(r3, 40), i3);
+    assert_eq!(slab.len(), 2);
+    assert_eq!(slab[i1], 30);
+    assert_eq!(slab[i3], 40);
+    assert_eq!(slab.insert(50), 2);
+}
+
+#[test]
+#[should_panic(expected = "invalid slab reservation 0")]
+fn slab_reserve_slot_cleared() {
+    let mut slab = Slab::new();
+    let reservation = slab.reserve_slot();
+    slab.clear();
+    slab.fill(reservation, 1);
+}
+
+#[test]
+fn slab_reserve_slot_reserve() {
+    let mut slab: Slab<i32> = Slab::with_capacity(4);
+    let reservations: Vec<_> = (0..4).map(|_| slab.reserve_slot()).collect();
+    slab.reserve(4);
+    assert!(slab.capacity() >= 8);
+
+    // Explicit insertions leave the reserved slots alone
+    let (obj, err) = slab.insert_at(reservations[0].index(), 1).unwrap_err();
+    assert_eq!((obj, err), (1, InsertAtError::SlotReserved));
+    assert!(slab.is_empty());
+    let mut reservations = reservations.into_iter();
+    slab.fill(reservations.next().unwrap(), 1);
+    for reservation in reservations {
+        slab.cancel(reservation);
+    }
+    assert_eq!(slab[0], 1);
+    assert_eq!(slab.insert(2), 3);
+}
+
+#[test]
+#[should_panic(expected = "reserved slab slot 0")]
+fn slab_entry_reserved() {
+    let mut slab = Slab::new();
+    let reservation = slab.reserve_slot();
+    slab.entry(reservation.index()).or_insert(1);
+}
+
+#[test]
+fn slab_insert_many() {
+    let mut slab = Slab::new();
+    let keys = slab.insert_many(0..4);
+    assert_eq!(keys, vec![0, 1, 2, 3]);
+    slab.remove(1);
+    slab.remove(2);
+
+    // Fills the free slots first, even with a lower size hint
+    let keys = slab.insert_many((4..8).filter(|_| true));
+    assert_eq!(keys.len(), 4);
+    assert_eq!(keys[2..], [4, 5]);
+    assert_eq!(slab.len(), 6);
+
+    slab.extend(vec![8, 9]);
+    assert_eq!(slab.len(), 8);
+    assert_eq!(slab[7], 9);
+
+    let slab: Slab<_, u16> = (0..10).map(|i| i * 2).collect();
+    assert_eq!(slab.len(), 10);
+    let objs: Vec<_> = slab.into_iter().collect();
+    assert_eq!(objs[9], (9, 18));
+}
+
+#[test]
+fn slab_from_indexed_iter() {
+    let mut slab: Slab<_> = Slab::from_indexed_iter(vec![(5, 'f'), (1, 'b'), (3, 'd')]).unwrap();
+    assert_eq!(slab.len(), 3);
+    assert_eq!(slab.get(0), None);
+    assert_eq!(slab[3], 'd');
+
+    // The gaps are reused in ascending order before growing the slab
+    assert_eq!(slab.insert('a'), 0);
+    assert_eq!(slab.insert('c'), 2);
+    assert_eq!(slab.insert('e'), 4);
+    assert_eq!(slab.insert('g'), 6);
+
+    let err = Slab::<_>::from_indexed_iter(vec![(0, 'a'), (2, 'c'), (0, 'b')]).unwrap_err();
+    assert_eq!(err.key(), 0);
+    assert_eq!(err.to_string(), "duplicate slab key 0");
+
+    // Restores a snapshot, which reuses the same free slots
+    slab.remove(2);
+    slab.remove(4);
+    let snapshot: Vec<_> = slab.iter().map(|(key, &obj)| (key as u32, obj)).collect();
+    let mut restored: Slab<char, u32> = snapshot.into_iter().collect();
+    assert_eq!(restored.len(), 5);
+    assert_eq!(restored.insert('x'), 2);
+    assert_eq!(restored.insert('y'), 4);
+    assert_eq!(restored.insert('z'), 7);
+}
+
+#[test]
+fn slab_clone_eq_hash() {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    fn hash<T: Hash>(obj: &T) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        obj.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    let mut slab: Slab<_> = (0..6).collect();
+    slab.remove(4);
+    slab.remove(1);
+    slab.remove(3);
+    slab.remove(5);
+    let reservation = slab.reserve_slot();
+    let mut copy = slab.clone();
+    assert_eq!(copy, slab);
+    assert_eq!(hash(&copy), hash(&slab));
+
+    // The free slots are reused in the same order
+    for i in 0..3 {
+        assert_eq!(copy.insert(i), slab.insert(i));
+    }
+    let reserved = reservation.index();
+    assert_eq!(
+        copy.insert_at(reserved, 3),
+        Err((3, InsertAtError::SlotReserved))
+    );
+    slab.fill(reservation, 3);
+    assert_ne!(copy, slab);
+
+    // Equality ignores the free slots and the capacity
+    let mut other: Slab<_> = Slab::with_capacity(16);
+    other.extend(slab.iter().map(|(_, &obj)| obj));
+    let extra = other.insert(9);
+    other.remove(extra);
+    assert_eq!(other, slab);
+    assert_eq!(hash(&other), hash(&slab));
+    other[2] = 9;
+    assert_ne!(other, slab);
+
+    slab.remove(2);
+    copy.clone_from(&slab);
+    assert_eq!(copy, slab);
+    assert_eq!(copy.insert(4), slab.insert(4));
+}